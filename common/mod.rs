@@ -1,5 +1,18 @@
+// Each test crate compiles this module separately and uses only some of its helpers.
+#![allow(dead_code)]
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use pacm::cache::{CasStore, StoreEntry};
+use pacm::lockfile::{Lockfile, PackageEntry};
+use std::collections::BTreeMap;
 use std::env;
 use std::ffi::OsString;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Held by every [`DataHomeGuard`]; the variables it sets are process-wide, so guarded tests
+/// in one binary must not overlap.
+static ENV_LOCK: Mutex<()> = Mutex::new(());
 
 /// Guards environment variables so pacm data paths resolve inside a temporary sandbox.
 pub struct DataHomeGuard {
@@ -8,11 +21,15 @@ pub struct DataHomeGuard {
     prev_local: Option<OsString>,
     prev_appdata: Option<OsString>,
     prev_home: Option<OsString>,
+    // Declared last so the lock is released only after the sandbox is restored and removed.
+    _lock: MutexGuard<'static, ()>,
 }
 
 impl DataHomeGuard {
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
+        // A test that panicked while holding the lock still restored the env on unwind.
+        let lock = ENV_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
         let temp = tempfile::tempdir().expect("create test tempdir");
         let data_home = temp.path().join("data-home");
         std::fs::create_dir_all(&data_home).expect("create data-home dir");
@@ -29,7 +46,7 @@ impl DataHomeGuard {
         let prev_home = env::var_os("HOME");
         env::set_var("HOME", temp.path());
 
-        Self { _temp: temp, prev_xdg, prev_local, prev_appdata, prev_home }
+        Self { _temp: temp, prev_xdg, prev_local, prev_appdata, prev_home, _lock: lock }
     }
 }
 
@@ -49,3 +66,68 @@ fn restore_env(key: &str, previous: &Option<OsString>) {
         None => env::remove_var(key),
     }
 }
+
+/// Returns `{prefix}-{n}`, with `n` unique within the test binary.
+pub fn unique_package(prefix: &str) -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{prefix}-{}", id)
+}
+
+/// Builds a lockfile entry with only `version` and `dependencies` set; tests override the
+/// remaining fields with struct update syntax.
+pub fn package_entry(version: &str, deps: &[(&str, &str)]) -> PackageEntry {
+    PackageEntry {
+        version: Some(version.to_string()),
+        integrity: None,
+        resolved: None,
+        dependencies: deps.iter().map(|(n, r)| (n.to_string(), r.to_string())).collect(),
+        dev_dependencies: BTreeMap::new(),
+        optional_dependencies: BTreeMap::new(),
+        peer_dependencies: BTreeMap::new(),
+        peer_dependencies_meta: BTreeMap::new(),
+        os: Vec::new(),
+        cpu_arch: Vec::new(),
+        libc: Vec::new(),
+        store_key: None,
+        content_hash: None,
+        link_mode: None,
+        store_path: None,
+        source: None,
+    }
+}
+
+/// Like [`package_entry`], plus the registry `resolved` URL and `integrity` most lockfile
+/// fixtures carry.
+pub fn resolved_entry(version: &str, deps: &[(&str, &str)]) -> PackageEntry {
+    PackageEntry {
+        integrity: Some(format!("sha512-{version}")),
+        resolved: Some(format!("https://registry.example/pkg-{version}.tgz")),
+        ..package_entry(version, deps)
+    }
+}
+
+/// A well-formed sha512 SRI string; `seed` picks the digest bytes.
+pub fn valid_integrity(seed: u8) -> String {
+    format!("sha512-{}", STANDARD.encode([seed; 64]))
+}
+
+/// Like [`resolved_entry`], but with an integrity string that passes validation.
+pub fn valid_resolved_entry(version: &str, deps: &[(&str, &str)]) -> PackageEntry {
+    PackageEntry { integrity: Some(valid_integrity(1)), ..resolved_entry(version, deps) }
+}
+
+/// Collects `(install path, entry)` pairs into a lockfile.
+pub fn lockfile(entries: Vec<(&str, PackageEntry)>) -> Lockfile {
+    let mut lf = Lockfile::default();
+    for (key, entry) in entries {
+        lf.packages.insert(key.to_string(), entry);
+    }
+    lf
+}
+
+/// Lockfiles record store paths relative to the store root, always with `/` separators.
+pub fn portable_store_path(store: &CasStore, entry: &StoreEntry) -> String {
+    let relative = entry.root_dir.strip_prefix(store.root()).expect("entry inside store root");
+    relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
@@ -1,13 +1,16 @@
 mod common;
 
-use common::DataHomeGuard;
+use common::{package_entry, portable_store_path, unique_package, DataHomeGuard};
 use pacm::cache::{cache_package_path, CasStore, EnsureParams, StoreEntry};
-use pacm::installer::{InstallMode, InstallPlanEntry, Installer, PackageInstance};
-use pacm::lockfile::{Lockfile, PackageEntry};
+use pacm::installer::report::render_json;
+use pacm::installer::{
+    InstallAction, InstallMode, InstallOutcome, InstallPlanEntry, Installer, PackageInstance,
+    Platform,
+};
+use pacm::lockfile::{encode_current_binary, Lockfile, PackageEntry};
 use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicUsize, Ordering};
 use tempfile::tempdir;
 
 fn prepare_cached_package(name: &str, version: &str) -> PathBuf {
@@ -24,20 +27,9 @@ fn prepare_cached_package(name: &str, version: &str) -> PathBuf {
 
 fn lock_entry(version: &str, integrity: &str) -> PackageEntry {
     PackageEntry {
-        version: Some(version.to_string()),
         integrity: Some(integrity.to_string()),
         resolved: Some(format!("https://example.com/{version}.tgz")),
-        dependencies: BTreeMap::new(),
-        dev_dependencies: BTreeMap::new(),
-        optional_dependencies: BTreeMap::new(),
-        peer_dependencies: BTreeMap::new(),
-        peer_dependencies_meta: BTreeMap::new(),
-        os: Vec::new(),
-        cpu_arch: Vec::new(),
-        store_key: None,
-        content_hash: None,
-        link_mode: None,
-        store_path: None,
+        ..package_entry(version, &[])
     }
 }
 
@@ -51,12 +43,6 @@ fn package_instance(name: &str, version: &str) -> PackageInstance {
     }
 }
 
-fn unique_package(prefix: &str) -> String {
-    static COUNTER: AtomicUsize = AtomicUsize::new(0);
-    let id = COUNTER.fetch_add(1, Ordering::Relaxed);
-    format!("{prefix}-{}", id)
-}
-
 fn node_modules_path(root: &Path, package_name: &str) -> PathBuf {
     let mut path = root.join("node_modules");
     for part in package_name.split('/') {
@@ -65,6 +51,58 @@ fn node_modules_path(root: &Path, package_name: &str) -> PathBuf {
     path
 }
 
+fn ensure_store_entry(store: &CasStore, name: &str, version: &str) -> StoreEntry {
+    let pkg_dir = prepare_cached_package(name, version);
+    let integrity = format!("sha512-{name}-{version}");
+    let resolved = format!("https://example.com/{name}-{version}.tgz");
+    let params = EnsureParams {
+        name,
+        version,
+        dependencies: &[],
+        source_dir: pkg_dir.as_path(),
+        integrity: Some(&integrity),
+        resolved: Some(&resolved),
+    };
+    store.ensure_entry(&params).expect("ensure store entry")
+}
+
+fn plan_entry(store_entry: &StoreEntry, name: &str, version: &str) -> InstallPlanEntry {
+    InstallPlanEntry { package: package_instance(name, version), store_entry: store_entry.clone() }
+}
+
+fn action_for(outcomes: &[InstallOutcome], name: &str) -> InstallAction {
+    outcomes
+        .iter()
+        .find(|o| o.package_name == name)
+        .map(|o| o.action)
+        .unwrap_or_else(|| panic!("no outcome reported for {name}"))
+}
+
+fn list_dir(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .expect("read dir")
+        .filter_map(Result::ok)
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+fn tree_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .expect("read dir")
+        .filter_map(Result::ok)
+        .map(|e| {
+            let meta = e.metadata().expect("stat entry");
+            if meta.is_dir() {
+                tree_size(&e.path())
+            } else {
+                meta.len()
+            }
+        })
+        .sum()
+}
+
 fn assert_store_contains(entry: &StoreEntry, filename: &str) {
     if entry.package_dir.join(filename).exists() {
         return;
@@ -124,11 +162,12 @@ fn installer_links_package_and_updates_lock() {
     assert!(installed_pkg.join("index.js").exists());
 
     let lock_entry = lock.packages.get(&lock_key).expect("lock entry updated");
-    let expected_path = store_entry.root_dir.to_string_lossy().to_string();
+    let expected_path = portable_store_path(&store, &store_entry);
     assert_eq!(lock_entry.store_key.as_deref(), Some(store_entry.store_key.as_str()));
     assert_eq!(lock_entry.content_hash.as_deref(), Some(store_entry.content_hash.as_str()));
     assert_eq!(lock_entry.link_mode.as_deref(), Some("link"));
     assert_eq!(lock_entry.store_path.as_deref(), Some(expected_path.as_str()));
+    assert_eq!(store.root().join(&expected_path), store_entry.root_dir);
 }
 
 #[test]
@@ -178,6 +217,380 @@ fn installer_copy_mode_materializes_files() {
     assert_eq!(lock_entry.link_mode.as_deref(), Some("copy"));
     assert_eq!(lock_entry.store_key.as_deref(), Some(store_entry.store_key.as_str()));
     assert_eq!(lock_entry.content_hash.as_deref(), Some(store_entry.content_hash.as_str()));
-    let expected_path = store_entry.root_dir.to_string_lossy().to_string();
+    let expected_path = portable_store_path(&store, &store_entry);
     assert_eq!(lock_entry.store_path.as_deref(), Some(expected_path.as_str()));
 }
+
+#[test]
+fn installer_reinstall_only_touches_changed_packages() {
+    let _sandbox = DataHomeGuard::new();
+    let kept = unique_package("kept");
+    let bumped = unique_package("bumped");
+    let dropped = unique_package("dropped");
+
+    let store = CasStore::open().expect("open cas store");
+    let kept_entry = ensure_store_entry(&store, &kept, "1.0.0");
+    let bumped_v1 = ensure_store_entry(&store, &bumped, "1.0.0");
+    let bumped_v2 = ensure_store_entry(&store, &bumped, "2.0.0");
+    let dropped_entry = ensure_store_entry(&store, &dropped, "1.0.0");
+
+    let mut lock = Lockfile::default();
+    for name in [&kept, &bumped, &dropped] {
+        lock.packages.insert(format!("node_modules/{name}"), lock_entry("1.0.0", "sha512-x"));
+    }
+    let mut plan = HashMap::new();
+    plan.insert(kept.clone(), plan_entry(&kept_entry, &kept, "1.0.0"));
+    plan.insert(bumped.clone(), plan_entry(&bumped_v1, &bumped, "1.0.0"));
+    plan.insert(dropped.clone(), plan_entry(&dropped_entry, &dropped, "1.0.0"));
+
+    let project = tempdir().expect("create project dir");
+    let installer = Installer::new(InstallMode::Copy);
+    let first = installer.install(project.path(), &plan, &mut lock).expect("initial install");
+    assert_eq!(first.len(), 3);
+    assert!(first.iter().all(|o| o.action == InstallAction::Added));
+
+    // A marker survives only if the installer leaves the directory alone.
+    let marker = node_modules_path(project.path(), &kept).join(".untouched");
+    fs::write(&marker, "").expect("write marker");
+
+    let second = installer.install(project.path(), &plan, &mut lock).expect("repeat install");
+    assert_eq!(second.len(), 3);
+    assert!(second.iter().all(|o| o.action == InstallAction::Skipped));
+
+    plan.insert(bumped.clone(), plan_entry(&bumped_v2, &bumped, "2.0.0"));
+    plan.remove(&dropped);
+    lock.packages.insert(format!("node_modules/{bumped}"), lock_entry("2.0.0", "sha512-y"));
+    lock.packages.remove(&format!("node_modules/{dropped}"));
+
+    let third = installer.install(project.path(), &plan, &mut lock).expect("incremental install");
+    assert_eq!(third.len(), 3);
+    assert_eq!(action_for(&third, &kept), InstallAction::Skipped);
+    assert_eq!(action_for(&third, &bumped), InstallAction::Updated);
+    assert_eq!(action_for(&third, &dropped), InstallAction::Removed);
+
+    assert!(marker.exists(), "unchanged package was rematerialized");
+    assert!(!node_modules_path(project.path(), &dropped).exists());
+    let manifest =
+        fs::read_to_string(node_modules_path(project.path(), &bumped).join("package.json"))
+            .expect("read updated package.json");
+    assert!(manifest.contains("2.0.0"));
+}
+
+#[test]
+fn installer_reinstalls_packages_missing_despite_state_file() {
+    let _sandbox = DataHomeGuard::new();
+    let kept = unique_package("kept");
+    let other = unique_package("other");
+
+    let store = CasStore::open().expect("open cas store");
+    let mut lock = Lockfile::default();
+    let mut plan = HashMap::new();
+    for name in [&kept, &other] {
+        let store_entry = ensure_store_entry(&store, name, "1.0.0");
+        lock.packages.insert(format!("node_modules/{name}"), lock_entry("1.0.0", "sha512-x"));
+        plan.insert(name.clone(), plan_entry(&store_entry, name, "1.0.0"));
+    }
+
+    let project = tempdir().expect("create project dir");
+    let installer = Installer::new(InstallMode::Copy);
+    installer.install(project.path(), &plan, &mut lock).expect("initial install");
+
+    // The state file still lists `kept`, but the tree on disk no longer has it.
+    let kept_dir = node_modules_path(project.path(), &kept);
+    fs::remove_dir_all(&kept_dir).expect("delete installed package");
+
+    let outcomes = installer.install(project.path(), &plan, &mut lock).expect("repeat install");
+    assert_eq!(action_for(&outcomes, &kept), InstallAction::Added);
+    assert_eq!(action_for(&outcomes, &other), InstallAction::Skipped);
+    assert!(kept_dir.join("index.js").exists(), "missing package was not reinstalled");
+}
+
+#[test]
+fn installer_link_mode_change_counts_as_update() {
+    let _sandbox = DataHomeGuard::new();
+    let name = unique_package("mode");
+    let store = CasStore::open().expect("open cas store");
+    let store_entry = ensure_store_entry(&store, &name, "1.0.0");
+
+    let mut lock = Lockfile::default();
+    let lock_key = format!("node_modules/{name}");
+    lock.packages.insert(lock_key.clone(), lock_entry("1.0.0", "sha512-x"));
+    let mut plan = HashMap::new();
+    plan.insert(name.clone(), plan_entry(&store_entry, &name, "1.0.0"));
+
+    let project = tempdir().expect("create project dir");
+    Installer::new(InstallMode::Link).install(project.path(), &plan, &mut lock).expect("link");
+    let outcomes = Installer::new(InstallMode::Copy)
+        .install(project.path(), &plan, &mut lock)
+        .expect("reinstall via copy mode");
+
+    assert_eq!(action_for(&outcomes, &name), InstallAction::Updated);
+    assert_eq!(outcomes[0].link_mode, InstallMode::Copy);
+    assert_eq!(lock.packages[&lock_key].link_mode.as_deref(), Some("copy"));
+}
+
+#[test]
+fn installer_parallel_outcomes_are_ordered() {
+    let _sandbox = DataHomeGuard::new();
+    let store = CasStore::open().expect("open cas store");
+    let names: Vec<String> = (0..12).map(|_| unique_package("par")).collect();
+
+    let mut lock = Lockfile::default();
+    let mut plan = HashMap::new();
+    for name in &names {
+        let store_entry = ensure_store_entry(&store, name, "1.0.0");
+        lock.packages.insert(format!("node_modules/{name}"), lock_entry("1.0.0", "sha512-x"));
+        plan.insert(name.clone(), plan_entry(&store_entry, name, "1.0.0"));
+    }
+
+    let mut expected = names.clone();
+    expected.sort();
+    for concurrency in [1, 4, 16] {
+        let project = tempdir().expect("create project dir");
+        let mut run_lock = lock.clone();
+        let outcomes = Installer::new(InstallMode::Copy)
+            .with_concurrency(concurrency)
+            .install(project.path(), &plan, &mut run_lock)
+            .expect("parallel install");
+
+        let order: Vec<String> = outcomes.iter().map(|o| o.package_name.clone()).collect();
+        assert_eq!(order, expected, "outcome order changed with concurrency {concurrency}");
+        for name in &names {
+            assert!(node_modules_path(project.path(), name).join("index.js").exists());
+        }
+    }
+}
+
+#[test]
+fn installer_parallel_reports_every_failure() {
+    let _sandbox = DataHomeGuard::new();
+    let store = CasStore::open().expect("open cas store");
+    let good = unique_package("good");
+    let broken_a = unique_package("broken");
+    let broken_b = unique_package("broken");
+
+    let project = tempdir().expect("create project dir");
+    let mut lock = Lockfile::default();
+    let mut plan = HashMap::new();
+    for name in [&good, &broken_a, &broken_b] {
+        let mut store_entry = ensure_store_entry(&store, name, "1.0.0");
+        if name != &good {
+            store_entry.package_dir = project.path().join("missing").join(name);
+        }
+        lock.packages.insert(format!("node_modules/{name}"), lock_entry("1.0.0", "sha512-x"));
+        plan.insert(name.clone(), plan_entry(&store_entry, name, "1.0.0"));
+    }
+
+    let err = Installer::new(InstallMode::Copy)
+        .with_concurrency(4)
+        .install(project.path(), &plan, &mut lock)
+        .expect_err("install should fail");
+    let message = format!("{err:#}");
+    assert!(message.contains("2 packages failed"), "unexpected error: {message}");
+    assert!(message.contains(&broken_a), "missing {broken_a} in: {message}");
+    assert!(message.contains(&broken_b), "missing {broken_b} in: {message}");
+    assert!(!message.contains(&good), "successful package reported as failed: {message}");
+}
+
+#[test]
+fn installer_failure_rolls_back_previous_tree() {
+    let _sandbox = DataHomeGuard::new();
+    let store = CasStore::open().expect("open cas store");
+    // Prefixes keep `existing` ahead of `zbroken` so the failure happens mid-plan.
+    let existing = unique_package("existing");
+    let broken = unique_package("zbroken");
+    let v1 = ensure_store_entry(&store, &existing, "1.0.0");
+    let v2 = ensure_store_entry(&store, &existing, "2.0.0");
+    let mut broken_entry = ensure_store_entry(&store, &broken, "1.0.0");
+
+    let project = tempdir().expect("create project dir");
+    let mut lock = Lockfile::default();
+    lock.packages.insert(format!("node_modules/{existing}"), lock_entry("1.0.0", "sha512-x"));
+    let mut plan = HashMap::new();
+    plan.insert(existing.clone(), plan_entry(&v1, &existing, "1.0.0"));
+    let installer = Installer::new(InstallMode::Copy).with_concurrency(1);
+    installer.install(project.path(), &plan, &mut lock).expect("initial install");
+
+    let node_modules = project.path().join("node_modules");
+    let tree_before = list_dir(&node_modules);
+
+    broken_entry.package_dir = project.path().join("missing");
+    plan.insert(existing.clone(), plan_entry(&v2, &existing, "2.0.0"));
+    plan.insert(broken.clone(), plan_entry(&broken_entry, &broken, "1.0.0"));
+    lock.packages.insert(format!("node_modules/{existing}"), lock_entry("2.0.0", "sha512-y"));
+    lock.packages.insert(format!("node_modules/{broken}"), lock_entry("1.0.0", "sha512-z"));
+    let mut attempted = lock.clone();
+
+    installer.install(project.path(), &plan, &mut attempted).expect_err("install should fail");
+
+    let manifest =
+        fs::read_to_string(node_modules_path(project.path(), &existing).join("package.json"))
+            .expect("read package.json after rollback");
+    assert!(manifest.contains("1.0.0"), "previous version was replaced: {manifest}");
+    assert!(!node_modules_path(project.path(), &broken).exists());
+    // No staging directories or partially written packages may be left behind.
+    assert_eq!(list_dir(&node_modules), tree_before);
+    // Install metadata is only written back to the lockfile on success.
+    assert_eq!(attempted, lock);
+    assert!(attempted.packages[&format!("node_modules/{existing}")].store_key.is_none());
+}
+
+fn darwin_only_lock(name: &str, optional: bool) -> Lockfile {
+    let mut lock = Lockfile::default();
+    let mut root = lock_entry("0.0.0", "sha512-root");
+    let deps = if optional { &mut root.optional_dependencies } else { &mut root.dependencies };
+    deps.insert(name.to_string(), "^1.0.0".to_string());
+    lock.packages.insert(String::new(), root);
+    let mut entry = lock_entry("1.0.0", "sha512-x");
+    entry.os = vec!["darwin".into()];
+    entry.cpu_arch = vec!["arm64".into(), "x64".into()];
+    lock.packages.insert(format!("node_modules/{name}"), entry);
+    lock
+}
+
+#[test]
+fn installer_skips_optional_packages_for_other_platforms() {
+    let _sandbox = DataHomeGuard::new();
+    let store = CasStore::open().expect("open cas store");
+    let name = unique_package("fsevents");
+    let store_entry = ensure_store_entry(&store, &name, "1.0.0");
+    let mut plan = HashMap::new();
+    plan.insert(name.clone(), plan_entry(&store_entry, &name, "1.0.0"));
+
+    let linux = Platform { os: "linux".into(), cpu: "x64".into(), libc: Some("glibc".into()) };
+    let project = tempdir().expect("create project dir");
+    let mut lock = darwin_only_lock(&name, true);
+    let outcomes = Installer::new(InstallMode::Copy)
+        .with_platform(linux)
+        .install(project.path(), &plan, &mut lock)
+        .expect("install for linux");
+    assert_eq!(action_for(&outcomes, &name), InstallAction::SkippedPlatform);
+    assert!(!node_modules_path(project.path(), &name).exists());
+    // The entry stays in the lockfile so other platforms still install it.
+    let entry = &lock.packages[&format!("node_modules/{name}")];
+    assert!(entry.store_key.is_none());
+
+    let darwin = Platform { os: "darwin".into(), cpu: "arm64".into(), libc: None };
+    let other_project = tempdir().expect("create project dir");
+    let mut lock = darwin_only_lock(&name, true);
+    let outcomes = Installer::new(InstallMode::Copy)
+        .with_platform(darwin)
+        .install(other_project.path(), &plan, &mut lock)
+        .expect("cross-install for darwin");
+    assert_eq!(action_for(&outcomes, &name), InstallAction::Added);
+    assert!(node_modules_path(other_project.path(), &name).join("package.json").exists());
+}
+
+#[test]
+fn installer_rejects_required_packages_for_other_platforms() {
+    let _sandbox = DataHomeGuard::new();
+    let store = CasStore::open().expect("open cas store");
+    let name = unique_package("native");
+    let store_entry = ensure_store_entry(&store, &name, "1.0.0");
+    let mut plan = HashMap::new();
+    plan.insert(name.clone(), plan_entry(&store_entry, &name, "1.0.0"));
+
+    let linux = Platform { os: "linux".into(), cpu: "x64".into(), libc: Some("glibc".into()) };
+    let project = tempdir().expect("create project dir");
+    let mut lock = darwin_only_lock(&name, false);
+    let err = Installer::new(InstallMode::Copy)
+        .with_platform(linux)
+        .install(project.path(), &plan, &mut lock)
+        .expect_err("required package must not be skipped");
+    let message = format!("{err:#}");
+    assert!(message.contains("unsupported platform"), "unexpected error: {message}");
+    assert!(message.contains(&name), "unexpected error: {message}");
+}
+
+#[test]
+fn installer_outcomes_record_bytes_and_store_path() {
+    let _sandbox = DataHomeGuard::new();
+    let store = CasStore::open().expect("open cas store");
+    let copied = unique_package("copied");
+    let linked = unique_package("linked");
+    let copied_entry = ensure_store_entry(&store, &copied, "1.0.0");
+    let linked_entry = ensure_store_entry(&store, &linked, "1.0.0");
+
+    let project = tempdir().expect("create project dir");
+    let mut lock = Lockfile::default();
+    let mut plan = HashMap::new();
+    lock.packages.insert(format!("node_modules/{copied}"), lock_entry("1.0.0", "sha512-x"));
+    plan.insert(copied.clone(), plan_entry(&copied_entry, &copied, "1.0.0"));
+    let outcomes = Installer::new(InstallMode::Copy)
+        .install(project.path(), &plan, &mut lock)
+        .expect("install via copy mode");
+    let outcome = &outcomes[0];
+    assert_eq!(outcome.bytes_copied, tree_size(&copied_entry.package_dir));
+    assert_eq!(outcome.bytes_linked, 0);
+    assert_eq!(outcome.store_path, copied_entry.root_dir);
+
+    // A fresh project keeps the removal of `copied` out of the link-mode outcomes.
+    let linked_project = tempdir().expect("create project dir");
+    let mut lock = Lockfile::default();
+    let mut plan = HashMap::new();
+    lock.packages.insert(format!("node_modules/{linked}"), lock_entry("1.0.0", "sha512-x"));
+    plan.insert(linked.clone(), plan_entry(&linked_entry, &linked, "1.0.0"));
+    let outcomes = Installer::new(InstallMode::Link)
+        .install(linked_project.path(), &plan, &mut lock)
+        .expect("install via link mode");
+    assert_eq!(outcomes.len(), 1);
+    let outcome = outcomes.iter().find(|o| o.package_name == linked).expect("linked outcome");
+    // Links may fall back to copies across devices, but every byte is accounted for once.
+    assert_eq!(outcome.bytes_linked + outcome.bytes_copied, tree_size(&linked_entry.package_dir));
+    assert_eq!(outcome.store_path, linked_entry.root_dir);
+}
+
+#[test]
+fn installer_report_serializes_outcomes_as_json() {
+    let _sandbox = DataHomeGuard::new();
+    let store = CasStore::open().expect("open cas store");
+    let name = unique_package("report");
+    let store_entry = ensure_store_entry(&store, &name, "1.0.0");
+
+    let project = tempdir().expect("create project dir");
+    let mut lock = Lockfile::default();
+    lock.packages.insert(format!("node_modules/{name}"), lock_entry("1.0.0", "sha512-x"));
+    let mut plan = HashMap::new();
+    plan.insert(name.clone(), plan_entry(&store_entry, &name, "1.0.0"));
+    let outcomes = Installer::new(InstallMode::Copy)
+        .install(project.path(), &plan, &mut lock)
+        .expect("install via copy mode");
+
+    let json = render_json(&outcomes).expect("render json report");
+    let report: serde_json::Value = serde_json::from_str(&json).expect("parse json report");
+    let packages = report["packages"].as_array().expect("packages array");
+    assert_eq!(packages.len(), 1);
+    let package = &packages[0];
+    assert_eq!(package["package_name"], name.as_str());
+    assert_eq!(package["action"], "added");
+    assert_eq!(package["link_mode"], "copy");
+    assert_eq!(package["bytes_copied"], outcomes[0].bytes_copied);
+    assert_eq!(package["bytes_linked"], 0);
+    assert_eq!(package["elapsed_ms"], outcomes[0].elapsed.as_millis() as u64);
+    assert_eq!(package["store_path"], store_entry.root_dir.to_string_lossy().as_ref());
+    assert_eq!(report["totals"]["bytes_copied"], outcomes[0].bytes_copied);
+}
+
+#[test]
+fn installer_lockfile_has_no_machine_specific_paths() {
+    let _sandbox = DataHomeGuard::new();
+    let store = CasStore::open().expect("open cas store");
+    let name = unique_package("portable");
+    let store_entry = ensure_store_entry(&store, &name, "1.0.0");
+
+    let project = tempdir().expect("create project dir");
+    let mut lock = Lockfile::default();
+    lock.packages.insert(format!("node_modules/{name}"), lock_entry("1.0.0", "sha512-x"));
+    let mut plan = HashMap::new();
+    plan.insert(name.clone(), plan_entry(&store_entry, &name, "1.0.0"));
+    Installer::new(InstallMode::Link).install(project.path(), &plan, &mut lock).expect("install");
+
+    let store_path = lock.packages[&format!("node_modules/{name}")].store_path.clone().unwrap();
+    assert!(!Path::new(&store_path).is_absolute(), "store path is absolute: {store_path}");
+    let encoded = encode_current_binary(&lock).expect("encode lockfile");
+    let root = store.root().to_string_lossy().into_owned();
+    assert!(
+        !encoded.windows(root.len()).any(|w| w == root.as_bytes()),
+        "encoded lockfile embeds the local store root {root}"
+    );
+}
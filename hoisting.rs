@@ -0,0 +1,123 @@
+use pacm::installer::hoist::{HoistOptions, HoistPlanner};
+use pacm::installer::PackageInstance;
+use std::collections::BTreeMap;
+
+fn instance(name: &str, version: &str, deps: &[(&str, &str)]) -> PackageInstance {
+    PackageInstance {
+        name: name.to_string(),
+        version: version.to_string(),
+        dependencies: deps.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect(),
+        optional_dependencies: BTreeMap::new(),
+        peer_dependencies: BTreeMap::new(),
+    }
+}
+
+fn root(deps: &[(&str, &str)]) -> BTreeMap<String, String> {
+    deps.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect()
+}
+
+fn placed_version<'a>(
+    placements: &'a BTreeMap<String, PackageInstance>,
+    path: &str,
+) -> Option<&'a str> {
+    placements.get(path).map(|p| p.version.as_str())
+}
+
+#[test]
+fn hoists_transitive_dependencies_to_root() {
+    let packages = vec![instance("a", "1.0.0", &[("b", "1.0.0")]), instance("b", "1.0.0", &[])];
+    let planner = HoistPlanner::new(HoistOptions::default());
+    let placements = planner.plan(&root(&[("a", "1.0.0")]), &packages).expect("plan hoisting");
+
+    let keys: Vec<&str> = placements.keys().map(String::as_str).collect();
+    assert_eq!(keys, vec!["node_modules/a", "node_modules/b"]);
+    assert_eq!(placed_version(&placements, "node_modules/b"), Some("1.0.0"));
+}
+
+#[test]
+fn conflicting_versions_nest_under_dependent() {
+    let packages = vec![
+        instance("a", "1.0.0", &[("b", "1.0.0")]),
+        instance("c", "1.0.0", &[("b", "2.0.0")]),
+        instance("b", "1.0.0", &[]),
+        instance("b", "2.0.0", &[]),
+    ];
+    let planner = HoistPlanner::new(HoistOptions::default());
+    let placements = planner
+        .plan(&root(&[("a", "1.0.0"), ("b", "2.0.0"), ("c", "1.0.0")]), &packages)
+        .expect("plan hoisting");
+
+    // The root's own requirement always owns the top-level slot.
+    assert_eq!(placed_version(&placements, "node_modules/b"), Some("2.0.0"));
+    assert_eq!(placed_version(&placements, "node_modules/a/node_modules/b"), Some("1.0.0"));
+    // `c` is satisfied by the hoisted copy and must not get a duplicate.
+    assert!(!placements.contains_key("node_modules/c/node_modules/b"));
+    assert_eq!(placements.len(), 4);
+}
+
+#[test]
+fn plan_is_independent_of_input_order() {
+    let mut packages = vec![
+        instance("a", "1.0.0", &[("shared", "1.0.0")]),
+        instance("b", "1.0.0", &[("shared", "2.0.0")]),
+        instance("shared", "1.0.0", &[]),
+        instance("shared", "2.0.0", &[]),
+    ];
+    let root_deps = root(&[("a", "1.0.0"), ("b", "1.0.0")]);
+    let planner = HoistPlanner::new(HoistOptions::default());
+
+    let forward = planner.plan(&root_deps, &packages).expect("plan forward order");
+    packages.reverse();
+    let reverse = planner.plan(&root_deps, &packages).expect("plan reverse order");
+
+    assert_eq!(forward, reverse);
+    // Ties between dependents are broken by install path, so `a` wins the root slot.
+    assert_eq!(placed_version(&forward, "node_modules/shared"), Some("1.0.0"));
+    assert_eq!(placed_version(&forward, "node_modules/b/node_modules/shared"), Some("2.0.0"));
+}
+
+#[test]
+fn hoist_pattern_negation_keeps_package_nested() {
+    let packages = vec![
+        instance("a", "1.0.0", &[("lodash", "4.17.21"), ("ms", "2.1.3")]),
+        instance("lodash", "4.17.21", &[]),
+        instance("ms", "2.1.3", &[]),
+    ];
+    let options = HoistOptions {
+        hoist_pattern: vec!["*".into(), "!lodash".into()],
+        public_hoist_pattern: Vec::new(),
+    };
+    let placements = HoistPlanner::new(options)
+        .plan(&root(&[("a", "1.0.0")]), &packages)
+        .expect("plan hoisting");
+
+    assert!(placements.contains_key("node_modules/ms"));
+    assert!(!placements.contains_key("node_modules/lodash"));
+    assert_eq!(placed_version(&placements, "node_modules/a/node_modules/lodash"), Some("4.17.21"));
+}
+
+#[test]
+fn public_hoist_pattern_forces_root_placement() {
+    let packages = vec![
+        instance("eslint-config-demo", "1.0.0", &[("eslint-plugin-import", "2.29.0")]),
+        instance("eslint-plugin-import", "2.29.0", &[("doctrine", "2.1.0")]),
+        instance("doctrine", "2.1.0", &[]),
+    ];
+    // The default hoists everything; an empty `hoist_pattern` hoists nothing, so only the
+    // public pattern can move a package to the root here.
+    assert_eq!(HoistOptions::default().hoist_pattern, vec![String::from("*")]);
+    let options = HoistOptions {
+        hoist_pattern: Vec::new(),
+        public_hoist_pattern: vec!["*eslint-plugin*".into()],
+    };
+    let placements = HoistPlanner::new(options)
+        .plan(&root(&[("eslint-config-demo", "1.0.0")]), &packages)
+        .expect("plan hoisting");
+
+    assert_eq!(placed_version(&placements, "node_modules/eslint-plugin-import"), Some("2.29.0"));
+    assert_eq!(
+        placed_version(&placements, "node_modules/eslint-plugin-import/node_modules/doctrine"),
+        Some("2.1.0")
+    );
+    assert!(!placements.contains_key("node_modules/doctrine"));
+}
@@ -1,3 +1,6 @@
+mod common;
+
+use common::resolved_entry;
 use pacm::lockfile::{load, write, Lockfile};
 use pacm::manifest::Manifest;
 
@@ -15,13 +18,15 @@ fn lockfile_sync() {
     assert!(loaded.packages.contains_key("node_modules/foo"));
 }
 
-use pacm::lockfile::{decode_current_binary, encode_current_binary, PackageEntry, PeerMeta};
+use pacm::lockfile::{
+    decode_current_binary, encode_current_binary, PackageEntry, PeerMeta, CURRENT_FORMAT,
+};
 use std::collections::BTreeMap;
 
 #[test]
 fn encode_decode_roundtrip() {
     let mut lf = Lockfile::default();
-    lf.format = 7;
+    lf.format = CURRENT_FORMAT;
     let mut entry = PackageEntry {
         version: Some("1.2.3".to_string()),
         integrity: Some("sha512-deadbeef".to_string()),
@@ -36,10 +41,12 @@ fn encode_decode_roundtrip() {
         )]),
         os: vec![String::from("linux")],
         cpu_arch: vec![String::from("x64")],
+        libc: vec![String::from("glibc")],
         store_key: None,
         content_hash: None,
         link_mode: None,
         store_path: None,
+        source: None,
     };
     lf.packages.insert(String::from(""), entry.clone());
     entry.version = Some("0.0.1".into());
@@ -51,3 +58,105 @@ fn encode_decode_roundtrip() {
     let decoded = decode_current_binary(&encoded).expect("decode");
     assert_eq!(lf, decoded);
 }
+
+#[test]
+fn frozen_lockfile_accepts_matching_manifest() {
+    let mut manifest = Manifest::new("demo".into(), "0.1.0".into());
+    manifest.dependencies.insert("foo".into(), "^1.0.0".into());
+    let mut lock = Lockfile::default();
+    lock.sync_from_manifest(&manifest);
+
+    lock.ensure_frozen(&manifest).expect("lockfile is up to date");
+}
+
+#[test]
+fn frozen_lockfile_reports_pending_changes() {
+    let mut manifest = Manifest::new("demo".into(), "0.1.0".into());
+    manifest.dependencies.insert("foo".into(), "^1.0.0".into());
+    manifest.dependencies.insert("old".into(), "^1.0.0".into());
+    let mut lock = Lockfile::default();
+    lock.sync_from_manifest(&manifest);
+    let before = lock.clone();
+
+    manifest.dependencies.remove("old");
+    manifest.dependencies.insert("bar".into(), "^2.0.0".into());
+    manifest.dependencies.insert("foo".into(), "^1.5.0".into());
+    let err = lock.ensure_frozen(&manifest).expect_err("manifest drifted from lockfile");
+
+    assert_eq!(err.added, vec![String::from("node_modules/bar")]);
+    assert_eq!(err.removed, vec![String::from("node_modules/old")]);
+    // The root entry records the manifest ranges, so the `foo` bump changes it.
+    assert_eq!(err.changed, vec![String::new()]);
+    let message = err.to_string();
+    assert!(message.contains("+ node_modules/bar"), "diff missing addition: {message}");
+    assert!(message.contains("- node_modules/old"), "diff missing removal: {message}");
+    // Checking must never modify the lockfile it inspects.
+    assert_eq!(lock, before);
+}
+
+fn synced_project() -> (Manifest, Lockfile) {
+    let mut manifest = Manifest::new("demo".into(), "0.1.0".into());
+    manifest.dependencies.insert("foo".into(), "^1.0.0".into());
+    manifest.dependencies.insert("bar".into(), "^1.0.0".into());
+    let mut lock = Lockfile::default();
+    lock.sync_from_manifest(&manifest);
+    lock.packages.insert("node_modules/foo".into(), resolved_entry("1.2.0", &[("shared", "^1")]));
+    lock.packages.insert(
+        "node_modules/bar".into(),
+        resolved_entry("1.0.0", &[("baz", "^1"), ("shared", "^1")]),
+    );
+    lock.packages.insert("node_modules/baz".into(), resolved_entry("1.0.0", &[]));
+    lock.packages.insert("node_modules/shared".into(), resolved_entry("1.5.0", &[]));
+    (manifest, lock)
+}
+
+#[test]
+fn sync_keeps_locked_versions_that_still_satisfy() {
+    let (mut manifest, mut lock) = synced_project();
+    manifest.dependencies.insert("foo".into(), "^1.1.0".into());
+    let before = lock.packages["node_modules/foo"].clone();
+
+    let summary = lock.sync_from_manifest(&manifest);
+    assert_eq!(lock.packages["node_modules/foo"], before);
+    assert!(summary.added.is_empty() && summary.removed.is_empty());
+    // Like `ensure_frozen`, the summary counts the root as changed when its ranges move.
+    assert_eq!(summary.updated, vec![String::new()]);
+    assert_eq!(lock.packages[""].dependencies["foo"], "^1.1.0");
+    assert_eq!(
+        summary.kept,
+        vec![String::from("node_modules/bar"), String::from("node_modules/foo")]
+    );
+}
+
+#[test]
+fn sync_reresolves_entries_outside_new_range() {
+    let (mut manifest, mut lock) = synced_project();
+    manifest.dependencies.insert("foo".into(), "^2.0.0".into());
+    manifest.dependencies.insert("qux".into(), "^3.0.0".into());
+
+    let summary = lock.sync_from_manifest(&manifest);
+    assert_eq!(summary.added, vec![String::from("node_modules/qux")]);
+    assert_eq!(summary.updated, vec![String::new(), String::from("node_modules/foo")]);
+    // The stale resolution is cleared so the resolver picks a matching version.
+    let foo = &lock.packages["node_modules/foo"];
+    assert_eq!(foo.version, None);
+    assert_eq!(foo.integrity, None);
+    assert_eq!(lock.packages[""].dependencies["foo"], "^2.0.0");
+}
+
+#[test]
+fn sync_drops_removed_dependencies_and_orphans() {
+    let (mut manifest, mut lock) = synced_project();
+    manifest.dependencies.remove("bar");
+
+    let summary = lock.sync_from_manifest(&manifest);
+    assert_eq!(
+        summary.removed,
+        vec![String::from("node_modules/bar"), String::from("node_modules/baz")]
+    );
+    assert_eq!(summary.updated, vec![String::new()]);
+    let keys: Vec<&str> = lock.packages.keys().map(String::as_str).collect();
+    // `shared` is still reachable through `foo`.
+    assert_eq!(keys, vec!["", "node_modules/foo", "node_modules/shared"]);
+    assert!(!lock.packages[""].dependencies.contains_key("bar"));
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::{lockfile, resolved_entry};
+use pacm::lockfile::diff::FieldChange;
+use pacm::lockfile::Lockfile;
+
+fn before() -> Lockfile {
+    lockfile(vec![
+        ("", resolved_entry("1.0.0", &[("foo", "^1.0.0"), ("old", "^1.0.0")])),
+        ("node_modules/foo", resolved_entry("1.0.0", &[("a", "^1.0.0"), ("b", "^1.0.0")])),
+        ("node_modules/old", resolved_entry("1.0.0", &[])),
+    ])
+}
+
+#[test]
+fn identical_lockfiles_have_empty_diff() {
+    let diff = before().diff(&before());
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "");
+}
+
+#[test]
+fn diff_reports_added_and_removed_packages() {
+    let mut after = before();
+    after.packages.remove("node_modules/old");
+    after.packages.insert("node_modules/zeta".into(), resolved_entry("3.0.0", &[]));
+    after.packages.insert("node_modules/alpha".into(), resolved_entry("2.0.0", &[]));
+
+    let diff = before().diff(&after);
+    assert_eq!(diff.added, vec!["node_modules/alpha".to_string(), "node_modules/zeta".to_string()]);
+    assert_eq!(diff.removed, vec!["node_modules/old".to_string()]);
+    assert!(diff.changed.is_empty());
+}
+
+#[test]
+fn diff_reports_field_changes_per_package() {
+    let mut after = before();
+    let mut foo = resolved_entry("1.1.0", &[("b", "^2.0.0"), ("c", "^1.0.0")]);
+    foo.resolved = Some("https://mirror.example/foo-1.1.0.tgz".into());
+    after.packages.insert("node_modules/foo".into(), foo);
+
+    let diff = before().diff(&after);
+    assert!(diff.added.is_empty() && diff.removed.is_empty());
+    assert_eq!(diff.changed.len(), 1);
+    let change = &diff.changed[0];
+    assert_eq!(change.key, "node_modules/foo");
+    assert_eq!(
+        change.fields,
+        vec![
+            FieldChange::Version { from: Some("1.0.0".into()), to: Some("1.1.0".into()) },
+            FieldChange::Integrity {
+                from: Some("sha512-1.0.0".into()),
+                to: Some("sha512-1.1.0".into()),
+            },
+            FieldChange::Resolved {
+                from: Some("https://registry.example/pkg-1.0.0.tgz".into()),
+                to: Some("https://mirror.example/foo-1.1.0.tgz".into()),
+            },
+            FieldChange::Dependency { name: "a".into(), from: Some("^1.0.0".into()), to: None },
+            FieldChange::Dependency {
+                name: "b".into(),
+                from: Some("^1.0.0".into()),
+                to: Some("^2.0.0".into()),
+            },
+            FieldChange::Dependency { name: "c".into(), from: None, to: Some("^1.0.0".into()) },
+        ]
+    );
+}
+
+#[test]
+fn diff_ignores_local_install_metadata() {
+    let mut after = before();
+    let foo = after.packages.get_mut("node_modules/foo").unwrap();
+    foo.store_key = Some("foo@1.0.0::abc".into());
+    foo.link_mode = Some("copy".into());
+    assert!(before().diff(&after).is_empty());
+}
+
+#[test]
+fn diff_renders_for_review() {
+    let mut after = before();
+    after.packages.remove("node_modules/old");
+    after.packages.insert("node_modules/alpha".into(), resolved_entry("2.0.0", &[]));
+    after.packages.get_mut("node_modules/foo").unwrap().version = Some("1.1.0".into());
+
+    let rendered = before().diff(&after).to_string();
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "+ node_modules/alpha 2.0.0",
+            "- node_modules/old 1.0.0",
+            "~ node_modules/foo",
+            "    version: 1.0.0 -> 1.1.0",
+        ]
+    );
+}
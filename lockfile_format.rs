@@ -0,0 +1,227 @@
+mod common;
+
+use common::resolved_entry;
+use pacm::lockfile::{
+    decode_current_binary, encode_binary_with_format, encode_current_binary, load,
+    load_with_report, write, Lockfile, LockfileError, WriteStatus, CURRENT_FORMAT,
+    SUPPORTED_FORMATS,
+};
+use std::fs;
+use std::path::Path;
+
+fn sample_lockfile() -> Lockfile {
+    let mut lf = Lockfile::default();
+    lf.packages.insert(String::new(), resolved_entry("1.0.0", &[("dep", "^1.0.0")]));
+    lf.packages.insert("node_modules/dep".into(), resolved_entry("1.4.2", &[]));
+    lf
+}
+
+/// Small deterministic xorshift generator so the fuzz corpus is reproducible.
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[test]
+fn current_format_loads_without_upgrade() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pacm.lockb");
+    fs::write(&path, encode_current_binary(&sample_lockfile()).unwrap()).unwrap();
+
+    let loaded = load_with_report(&path).expect("load current lockfile");
+    assert_eq!(loaded.upgraded_from, None);
+    assert_eq!(loaded.lockfile.format, CURRENT_FORMAT);
+}
+
+#[test]
+fn historical_formats_are_upgraded_in_memory() {
+    // Every format ever released keeps a dedicated decoder.
+    let expected: Vec<u32> = (1..=CURRENT_FORMAT).collect();
+    assert_eq!(SUPPORTED_FORMATS, expected.as_slice());
+
+    let dir = tempfile::tempdir().unwrap();
+    let lf = sample_lockfile();
+    for &format in SUPPORTED_FORMATS.iter().filter(|&&f| f != CURRENT_FORMAT) {
+        let path = dir.path().join(format!("v{format}.lockb"));
+        let bytes = encode_binary_with_format(&lf, format).expect("encode historical format");
+        fs::write(&path, &bytes).unwrap();
+
+        let loaded = load_with_report(&path).expect("load historical lockfile");
+        assert_eq!(loaded.upgraded_from, Some(format));
+        assert_eq!(loaded.lockfile.format, CURRENT_FORMAT);
+        let notice = loaded.notice().expect("upgrade notice");
+        assert!(notice.contains(&format!("lockfile upgraded from v{format}")), "{notice}");
+        // Older formats may lack newer fields, but package identity always survives.
+        for (key, original) in &lf.packages {
+            let upgraded = &loaded.lockfile.packages[key];
+            assert_eq!(upgraded.version, original.version, "{key} version in v{format}");
+            assert_eq!(upgraded.dependencies, original.dependencies, "{key} deps in v{format}");
+        }
+        // Upgrading happens in memory only; the file on disk is untouched.
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+    }
+}
+
+#[test]
+fn newer_formats_are_refused() {
+    let mut bytes = encode_current_binary(&sample_lockfile()).unwrap();
+    // The format version is the little-endian u32 right after the 8-byte magic.
+    bytes[8..12].copy_from_slice(&(CURRENT_FORMAT + 1).to_le_bytes());
+
+    let err = decode_current_binary(&bytes).expect_err("future format must not decode");
+    assert_eq!(err, LockfileError::UnsupportedFormat { found: CURRENT_FORMAT + 1 });
+    let message = err.to_string();
+    assert!(message.contains(&format!("v{}", CURRENT_FORMAT + 1)), "{message}");
+    assert!(message.contains("upgrade pacm"), "{message}");
+}
+
+#[test]
+fn unknown_historical_format_is_refused() {
+    let mut bytes = encode_current_binary(&sample_lockfile()).unwrap();
+    bytes[8..12].copy_from_slice(&0u32.to_le_bytes());
+    let err = decode_current_binary(&bytes).expect_err("format 0 never existed");
+    assert_eq!(err, LockfileError::UnsupportedFormat { found: 0 });
+}
+
+#[test]
+fn flipped_bits_are_detected() {
+    let bytes = encode_current_binary(&sample_lockfile()).unwrap();
+    // The checksum covers the format version too, so a flipped version bit can never
+    // hand a current payload to a historical decoder.
+    for index in 8..bytes.len() {
+        for bit in 0..8 {
+            let mut corrupted = bytes.clone();
+            corrupted[index] ^= 1 << bit;
+            match decode_current_binary(&corrupted) {
+                Err(LockfileError::Corrupt { .. })
+                | Err(LockfileError::UnsupportedFormat { .. }) => {}
+                other => panic!("bit {bit} of byte {index} flipped: got {other:?}"),
+            }
+        }
+    }
+}
+
+#[test]
+fn unchecksummed_lockfiles_still_load() {
+    // Format 7 is the layout written before checksums were added.
+    const LAST_UNCHECKSUMMED_FORMAT: u32 = 7;
+    assert!(CURRENT_FORMAT > LAST_UNCHECKSUMMED_FORMAT);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pacm.lockb");
+    let lf = sample_lockfile();
+    let bytes = encode_binary_with_format(&lf, LAST_UNCHECKSUMMED_FORMAT).unwrap();
+    fs::write(&path, bytes).unwrap();
+
+    let loaded = load_with_report(&path).expect("pre-checksum lockfile is not corrupt");
+    assert_eq!(loaded.upgraded_from, Some(LAST_UNCHECKSUMMED_FORMAT));
+    assert_eq!(loaded.lockfile.packages, lf.packages);
+}
+
+#[test]
+fn payload_corruption_reports_offset() {
+    let bytes = encode_current_binary(&sample_lockfile()).unwrap();
+    let mut corrupted = bytes.clone();
+    let middle = bytes.len() / 2;
+    corrupted[middle] ^= 0xff;
+    match decode_current_binary(&corrupted) {
+        Err(LockfileError::Corrupt { offset }) => assert!(offset < bytes.len()),
+        other => panic!("expected corruption error, got {other:?}"),
+    }
+}
+
+#[test]
+fn truncated_lockfiles_are_corrupt() {
+    let bytes = encode_current_binary(&sample_lockfile()).unwrap();
+    for len in 12..bytes.len() {
+        match decode_current_binary(&bytes[..len]) {
+            Err(LockfileError::Corrupt { offset }) => assert!(offset <= len, "len {len}"),
+            other => panic!("truncated to {len} bytes: expected corruption, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn decode_never_panics_on_arbitrary_input() {
+    let valid = encode_current_binary(&sample_lockfile()).unwrap();
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut corpus: Vec<Vec<u8>> = vec![Vec::new(), b"PACMLOCK".to_vec(), valid[..12].to_vec()];
+    for _ in 0..512 {
+        let len = (xorshift(&mut state) % 256) as usize;
+        corpus.push((0..len).map(|_| xorshift(&mut state) as u8).collect());
+    }
+    for _ in 0..512 {
+        // Keep the magic so mutations reach the version check and payload decoder.
+        let mut mutated = valid.clone();
+        for _ in 0..=(xorshift(&mut state) % 8) {
+            let index = 8 + (xorshift(&mut state) as usize) % (mutated.len() - 8);
+            mutated[index] = xorshift(&mut state) as u8;
+        }
+        corpus.push(mutated);
+    }
+
+    for input in &corpus {
+        // Only the absence of panics matters; random input may not decode at all.
+        let _ = decode_current_binary(input);
+    }
+}
+
+#[test]
+fn encoding_matches_golden_bytes() {
+    // A committed fixture pins the exact bytes, catching endianness, `usize` width and
+    // field-order drift across platforms. Regenerate with `PACM_UPDATE_FIXTURES=1` only
+    // when the format version changes.
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sample.lockb");
+    let bytes = encode_current_binary(&sample_lockfile()).unwrap();
+    if std::env::var_os("PACM_UPDATE_FIXTURES").is_some() {
+        fs::create_dir_all(fixture.parent().unwrap()).unwrap();
+        fs::write(&fixture, &bytes).unwrap();
+    }
+
+    let golden = fs::read(&fixture).unwrap_or_else(|err| {
+        panic!("read {}: {err}; run with PACM_UPDATE_FIXTURES=1", fixture.display())
+    });
+    assert_eq!(bytes, golden, "encoding of sample_lockfile() changed");
+    assert_eq!(decode_current_binary(&golden).unwrap(), sample_lockfile());
+    // Decoding and re-encoding must be a fixed point.
+    assert_eq!(encode_current_binary(&decode_current_binary(&bytes).unwrap()).unwrap(), bytes);
+}
+
+#[test]
+fn write_skips_unchanged_content() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pacm.lockb");
+    let lf = sample_lockfile();
+
+    assert_eq!(write(&lf, path.clone()).unwrap(), WriteStatus::Written);
+    let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+    assert_eq!(write(&lf.clone(), path.clone()).unwrap(), WriteStatus::Unchanged);
+    assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), modified);
+
+    let mut changed = lf.clone();
+    changed.packages.insert("node_modules/new".into(), resolved_entry("2.0.0", &[]));
+    assert_eq!(write(&changed, path.clone()).unwrap(), WriteStatus::Written);
+    assert_eq!(load(&path).unwrap(), changed);
+}
+
+#[test]
+fn write_leaves_no_temporary_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pacm.lockb");
+    let mut lf = sample_lockfile();
+    for version in ["1.0.0", "1.1.0", "1.2.0"] {
+        lf.packages.insert("node_modules/dep".into(), resolved_entry(version, &[]));
+        write(&lf, path.clone()).unwrap();
+    }
+
+    let names: Vec<String> = fs::read_dir(dir.path())
+        .unwrap()
+        .filter_map(Result::ok)
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(names, vec![String::from("pacm.lockb")]);
+    assert_eq!(fs::read(&path).unwrap(), encode_current_binary(&lf).unwrap());
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{lockfile, package_entry};
+use pacm::lockfile::graph::DependencyKind;
+use pacm::lockfile::Lockfile;
+
+/// root -> a -> (nested b@1, c -> b); root -> b@2; root -(dev)-> @s/x -> b
+fn nested_lockfile() -> Lockfile {
+    let mut root = package_entry("1.0.0", &[("a", "^1"), ("b", "^2")]);
+    root.dev_dependencies.insert("@s/x".into(), "^1".into());
+    lockfile(vec![
+        ("", root),
+        ("node_modules/a", package_entry("1.0.0", &[("b", "^1"), ("c", "^1")])),
+        ("node_modules/a/node_modules/b", package_entry("1.0.0", &[])),
+        ("node_modules/a/node_modules/c", package_entry("1.0.0", &[("b", "^1")])),
+        ("node_modules/b", package_entry("2.0.0", &[])),
+        ("node_modules/@s/x", package_entry("1.0.0", &[("b", "^2")])),
+    ])
+}
+
+#[test]
+fn resolves_edges_with_node_lookup_rules() {
+    let lf = nested_lockfile();
+    let graph = lf.graph();
+
+    assert_eq!(graph.resolve("", "b"), Some("node_modules/b"));
+    assert_eq!(graph.resolve("node_modules/a", "b"), Some("node_modules/a/node_modules/b"));
+    // Nested packages walk up through their ancestors' node_modules.
+    assert_eq!(
+        graph.resolve("node_modules/a/node_modules/c", "b"),
+        Some("node_modules/a/node_modules/b")
+    );
+    // Scope directories are not package boundaries.
+    assert_eq!(graph.resolve("node_modules/@s/x", "b"), Some("node_modules/b"));
+    assert_eq!(graph.resolve("node_modules/a", "missing"), None);
+}
+
+#[test]
+fn edges_carry_dependency_kind() {
+    let lf = nested_lockfile();
+    let graph = lf.graph();
+
+    let edges: Vec<(String, String, DependencyKind)> = graph
+        .edges_from("")
+        .into_iter()
+        .map(|e| (e.name.to_string(), e.to.expect("resolved edge").to_string(), e.kind))
+        .collect();
+    assert_eq!(
+        edges,
+        vec![
+            ("@s/x".into(), "node_modules/@s/x".into(), DependencyKind::Dev),
+            ("a".into(), "node_modules/a".into(), DependencyKind::Prod),
+            ("b".into(), "node_modules/b".into(), DependencyKind::Prod),
+        ]
+    );
+}
+
+#[test]
+fn computes_reverse_dependencies() {
+    let lf = nested_lockfile();
+    let graph = lf.graph();
+
+    assert_eq!(graph.dependents("node_modules/b"), vec!["", "node_modules/@s/x"]);
+    assert_eq!(
+        graph.dependents("node_modules/a/node_modules/b"),
+        vec!["node_modules/a", "node_modules/a/node_modules/c"]
+    );
+    assert!(graph.dependents("").is_empty());
+}
+
+#[test]
+fn detects_cycles_once() {
+    let lf = lockfile(vec![
+        ("", package_entry("1.0.0", &[("a", "^1")])),
+        ("node_modules/a", package_entry("1.0.0", &[("b", "^1")])),
+        ("node_modules/b", package_entry("1.0.0", &[("c", "^1")])),
+        ("node_modules/c", package_entry("1.0.0", &[("a", "^1")])),
+        ("node_modules/self", package_entry("1.0.0", &[("self", "^1")])),
+    ]);
+
+    assert_eq!(
+        lf.graph().cycles(),
+        vec![
+            vec![
+                String::from("node_modules/a"),
+                String::from("node_modules/b"),
+                String::from("node_modules/c"),
+            ],
+            vec![String::from("node_modules/self")],
+        ]
+    );
+    assert!(nested_lockfile().graph().cycles().is_empty());
+}
+
+#[test]
+fn why_lists_every_path_from_root() {
+    let lf = nested_lockfile();
+    let graph = lf.graph();
+
+    assert_eq!(
+        graph.why("b"),
+        vec![
+            vec![
+                String::from(""),
+                String::from("node_modules/@s/x"),
+                String::from("node_modules/b")
+            ],
+            vec![
+                String::from(""),
+                String::from("node_modules/a"),
+                String::from("node_modules/a/node_modules/b"),
+            ],
+            vec![
+                String::from(""),
+                String::from("node_modules/a"),
+                String::from("node_modules/a/node_modules/c"),
+                String::from("node_modules/a/node_modules/b"),
+            ],
+            vec![String::from(""), String::from("node_modules/b")],
+        ]
+    );
+    assert!(graph.why("not-installed").is_empty());
+}
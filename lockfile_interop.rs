@@ -0,0 +1,317 @@
+mod common;
+
+use common::resolved_entry;
+use pacm::lockfile::export::to_package_lock;
+use pacm::lockfile::import::{from_package_lock, from_pnpm_lock, from_yarn_lock};
+use pacm::lockfile::{Lockfile, PeerMeta};
+use pacm::manifest::Manifest;
+
+fn deps(lf: &Lockfile, key: &str) -> Vec<(String, String)> {
+    lf.packages[key].dependencies.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+}
+
+#[test]
+fn imports_package_lock_v3() {
+    let text = r#"{
+      "name": "demo",
+      "version": "1.0.0",
+      "lockfileVersion": 3,
+      "requires": true,
+      "packages": {
+        "": {
+          "name": "demo",
+          "version": "1.0.0",
+          "dependencies": { "foo": "^1.0.0" },
+          "devDependencies": { "jest": "^29.0.0" }
+        },
+        "node_modules/foo": {
+          "version": "1.2.0",
+          "resolved": "https://registry.npmjs.org/foo/-/foo-1.2.0.tgz",
+          "integrity": "sha512-foo",
+          "dependencies": { "bar": "^2.0.0" },
+          "optionalDependencies": { "fsevents": "^2.3.0" },
+          "peerDependencies": { "react": ">=17" },
+          "peerDependenciesMeta": { "react": { "optional": true } }
+        },
+        "node_modules/foo/node_modules/bar": {
+          "version": "2.0.1",
+          "resolved": "https://registry.npmjs.org/bar/-/bar-2.0.1.tgz",
+          "integrity": "sha512-bar"
+        },
+        "node_modules/fsevents": {
+          "version": "2.3.3",
+          "optional": true,
+          "os": ["darwin"],
+          "cpu": ["arm64", "x64"]
+        }
+      }
+    }"#;
+
+    let lf = from_package_lock(text).expect("import package-lock.json");
+    let keys: Vec<&str> = lf.packages.keys().map(String::as_str).collect();
+    assert_eq!(
+        keys,
+        vec!["", "node_modules/foo", "node_modules/foo/node_modules/bar", "node_modules/fsevents"]
+    );
+    assert_eq!(lf.packages[""].dev_dependencies["jest"], "^29.0.0");
+
+    let foo = &lf.packages["node_modules/foo"];
+    assert_eq!(foo.version.as_deref(), Some("1.2.0"));
+    assert_eq!(foo.integrity.as_deref(), Some("sha512-foo"));
+    assert_eq!(foo.resolved.as_deref(), Some("https://registry.npmjs.org/foo/-/foo-1.2.0.tgz"));
+    assert_eq!(deps(&lf, "node_modules/foo"), vec![("bar".into(), "^2.0.0".into())]);
+    assert_eq!(foo.optional_dependencies["fsevents"], "^2.3.0");
+    assert_eq!(foo.peer_dependencies["react"], ">=17");
+    assert_eq!(foo.peer_dependencies_meta["react"], PeerMeta { optional: true });
+
+    let fsevents = &lf.packages["node_modules/fsevents"];
+    assert_eq!(fsevents.os, vec!["darwin".to_string()]);
+    assert_eq!(fsevents.cpu_arch, vec!["arm64".to_string(), "x64".to_string()]);
+}
+
+#[test]
+fn imports_package_lock_v2_from_packages_section() {
+    // v2 carries both layouts; the legacy tree here disagrees on purpose.
+    let text = r#"{
+      "name": "demo",
+      "version": "1.0.0",
+      "lockfileVersion": 2,
+      "requires": true,
+      "packages": {
+        "": {
+          "name": "demo",
+          "version": "1.0.0",
+          "dependencies": { "foo": "^1.0.0" }
+        },
+        "node_modules/foo": {
+          "version": "1.2.0",
+          "resolved": "https://registry.npmjs.org/foo/-/foo-1.2.0.tgz",
+          "integrity": "sha512-foo",
+          "dependencies": { "bar": "^2.0.0" }
+        },
+        "node_modules/bar": {
+          "version": "2.0.1",
+          "resolved": "https://registry.npmjs.org/bar/-/bar-2.0.1.tgz",
+          "integrity": "sha512-bar"
+        }
+      },
+      "dependencies": {
+        "foo": {
+          "version": "1.0.0",
+          "resolved": "https://registry.npmjs.org/foo/-/foo-1.0.0.tgz",
+          "integrity": "sha512-legacy-foo",
+          "requires": { "bar": "^2.0.0" }
+        },
+        "bar": {
+          "version": "2.0.0",
+          "integrity": "sha512-legacy-bar"
+        },
+        "legacy-only": {
+          "version": "0.1.0",
+          "integrity": "sha512-legacy-only"
+        }
+      }
+    }"#;
+
+    let lf = from_package_lock(text).expect("import package-lock.json v2");
+    let keys: Vec<&str> = lf.packages.keys().map(String::as_str).collect();
+    assert_eq!(keys, vec!["", "node_modules/bar", "node_modules/foo"]);
+
+    let foo = &lf.packages["node_modules/foo"];
+    assert_eq!(foo.version.as_deref(), Some("1.2.0"));
+    assert_eq!(foo.integrity.as_deref(), Some("sha512-foo"));
+    assert_eq!(deps(&lf, "node_modules/foo"), vec![("bar".into(), "^2.0.0".into())]);
+    assert_eq!(lf.packages["node_modules/bar"].version.as_deref(), Some("2.0.1"));
+    assert_eq!(lf.packages["node_modules/bar"].integrity.as_deref(), Some("sha512-bar"));
+}
+
+#[test]
+fn rejects_package_lock_v1() {
+    let text = r#"{ "name": "demo", "lockfileVersion": 1, "dependencies": {} }"#;
+    let err = from_package_lock(text).expect_err("v1 has no packages section");
+    assert!(err.to_string().contains("lockfileVersion 1"), "unexpected error: {err}");
+}
+
+#[test]
+fn imports_yarn_classic_lock() {
+    let text = r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+bar@^2.0.0:
+  version "2.0.1"
+  resolved "https://registry.yarnpkg.com/bar/-/bar-2.0.1.tgz#0123abcd"
+  integrity sha512-bar
+
+foo@^1.0.0, foo@^1.1.0:
+  version "1.2.0"
+  resolved "https://registry.yarnpkg.com/foo/-/foo-1.2.0.tgz#4567ef01"
+  integrity sha512-foo
+  dependencies:
+    bar "^2.0.0"
+"#;
+    let mut manifest = Manifest::new("demo".into(), "1.0.0".into());
+    manifest.dependencies.insert("foo".into(), "^1.1.0".into());
+
+    let lf = from_yarn_lock(text, &manifest).expect("import yarn.lock v1");
+    assert_eq!(deps(&lf, ""), vec![("foo".into(), "^1.1.0".into())]);
+    let foo = &lf.packages["node_modules/foo"];
+    assert_eq!(foo.version.as_deref(), Some("1.2.0"));
+    assert_eq!(foo.integrity.as_deref(), Some("sha512-foo"));
+    assert_eq!(
+        foo.resolved.as_deref(),
+        Some("https://registry.yarnpkg.com/foo/-/foo-1.2.0.tgz#4567ef01")
+    );
+    assert_eq!(deps(&lf, "node_modules/foo"), vec![("bar".into(), "^2.0.0".into())]);
+    assert_eq!(lf.packages["node_modules/bar"].version.as_deref(), Some("2.0.1"));
+}
+
+#[test]
+fn imports_yarn_berry_lock() {
+    let text = r#"# This file is generated by running "yarn install" inside your project.
+
+__metadata:
+  version: 8
+  cacheKey: 10
+
+"demo@workspace:.":
+  version: 0.0.0-use.local
+  resolution: "demo@workspace:."
+  dependencies:
+    foo: "npm:^1.0.0"
+  languageName: unknown
+  linkType: soft
+
+"foo@npm:^1.0.0":
+  version: 1.2.0
+  resolution: "foo@npm:1.2.0"
+  checksum: 10/0123abcd
+  languageName: node
+  linkType: hard
+"#;
+    let mut manifest = Manifest::new("demo".into(), "0.0.0".into());
+    manifest.dependencies.insert("foo".into(), "^1.0.0".into());
+
+    let lf = from_yarn_lock(text, &manifest).expect("import berry yarn.lock");
+    let foo = &lf.packages["node_modules/foo"];
+    assert_eq!(foo.version.as_deref(), Some("1.2.0"));
+    // Berry checksums are not SRI hashes, so integrity is left for the first fetch.
+    assert_eq!(foo.integrity, None);
+    assert!(!lf.packages.keys().any(|k| k.contains("workspace")));
+}
+
+#[test]
+fn imports_pnpm_lock_v9() {
+    let text = r#"lockfileVersion: '9.0'
+
+importers:
+
+  .:
+    dependencies:
+      foo:
+        specifier: ^1.0.0
+        version: 1.2.0
+
+packages:
+
+  bar@2.0.1:
+    resolution: {integrity: sha512-bar}
+
+  foo@1.2.0:
+    resolution: {integrity: sha512-foo}
+
+snapshots:
+
+  bar@2.0.1: {}
+
+  foo@1.2.0:
+    dependencies:
+      bar: 2.0.1
+"#;
+
+    let lf = from_pnpm_lock(text).expect("import pnpm-lock.yaml");
+    assert_eq!(deps(&lf, ""), vec![("foo".into(), "^1.0.0".into())]);
+    let foo = &lf.packages["node_modules/foo"];
+    assert_eq!(foo.version.as_deref(), Some("1.2.0"));
+    assert_eq!(foo.integrity.as_deref(), Some("sha512-foo"));
+    assert_eq!(deps(&lf, "node_modules/foo"), vec![("bar".into(), "2.0.1".into())]);
+    assert_eq!(lf.packages["node_modules/bar"].integrity.as_deref(), Some("sha512-bar"));
+}
+
+fn exportable_project() -> (Manifest, Lockfile) {
+    let mut manifest = Manifest::new("demo".into(), "1.0.0".into());
+    manifest.dependencies.insert("foo".into(), "^1.0.0".into());
+    manifest.dev_dependencies.insert("jest".into(), "^29.0.0".into());
+
+    let mut lf = Lockfile::default();
+    lf.sync_from_manifest(&manifest);
+    let mut foo = resolved_entry("1.2.0", &[]);
+    foo.dependencies.insert("bar".into(), "^2.0.0".into());
+    foo.optional_dependencies.insert("fsevents".into(), "^2.3.0".into());
+    foo.peer_dependencies.insert("react".into(), ">=17".into());
+    foo.peer_dependencies_meta.insert("react".into(), PeerMeta { optional: true });
+    foo.store_key = Some("foo@1.2.0::abc".into());
+    foo.link_mode = Some("link".into());
+    let mut fsevents = resolved_entry("2.3.3", &[]);
+    fsevents.os = vec!["darwin".into()];
+    fsevents.cpu_arch = vec!["arm64".into(), "x64".into()];
+    lf.packages.insert("node_modules/foo".into(), foo);
+    lf.packages.insert("node_modules/foo/node_modules/bar".into(), resolved_entry("2.0.1", &[]));
+    lf.packages.insert("node_modules/fsevents".into(), fsevents);
+    lf.packages.insert("node_modules/jest".into(), resolved_entry("29.7.0", &[]));
+    (manifest, lf)
+}
+
+#[test]
+fn exports_package_lock_v3() {
+    let (manifest, lf) = exportable_project();
+    let text = to_package_lock(&lf, &manifest).expect("export package-lock.json");
+    let json: serde_json::Value = serde_json::from_str(&text).expect("parse exported json");
+
+    assert_eq!(json["name"], "demo");
+    assert_eq!(json["version"], "1.0.0");
+    assert_eq!(json["lockfileVersion"], 3);
+    assert_eq!(json["requires"], true);
+    let packages = &json["packages"];
+    assert_eq!(packages[""]["name"], "demo");
+    assert_eq!(packages[""]["dependencies"]["foo"], "^1.0.0");
+    assert_eq!(packages[""]["devDependencies"]["jest"], "^29.0.0");
+
+    let foo = &packages["node_modules/foo"];
+    assert_eq!(foo["version"], "1.2.0");
+    assert_eq!(foo["resolved"], "https://registry.example/pkg-1.2.0.tgz");
+    assert_eq!(foo["integrity"], "sha512-1.2.0");
+    assert_eq!(foo["dependencies"]["bar"], "^2.0.0");
+    assert_eq!(foo["optionalDependencies"]["fsevents"], "^2.3.0");
+    assert_eq!(foo["peerDependencies"]["react"], ">=17");
+    assert_eq!(foo["peerDependenciesMeta"]["react"]["optional"], true);
+    // pacm-only install metadata has no npm equivalent.
+    assert!(foo.get("store_key").is_none() && foo.get("storeKey").is_none());
+    assert!(foo.get("link_mode").is_none());
+
+    assert_eq!(packages["node_modules/fsevents"]["os"], serde_json::json!(["darwin"]));
+    assert_eq!(packages["node_modules/fsevents"]["cpu"], serde_json::json!(["arm64", "x64"]));
+    // npm flags packages that are only reachable through optional or dev edges.
+    assert_eq!(packages["node_modules/fsevents"]["optional"], true);
+    assert_eq!(packages["node_modules/jest"]["dev"], true);
+    assert!(packages["node_modules/foo"].get("dev").is_none());
+}
+
+#[test]
+fn exported_package_lock_imports_back() {
+    let (manifest, lf) = exportable_project();
+    let text = to_package_lock(&lf, &manifest).expect("export package-lock.json");
+    let mut imported = from_package_lock(&text).expect("import exported package-lock.json");
+
+    let mut expected = lf.clone();
+    for entry in expected.packages.values_mut() {
+        entry.store_key = None;
+        entry.link_mode = None;
+    }
+    // npm adds `name`/`version` to the root entry, so only its edges are compared.
+    let root = imported.packages.remove("").expect("root entry");
+    let expected_root = expected.packages.remove("").expect("root entry");
+    assert_eq!(root.dependencies, expected_root.dependencies);
+    assert_eq!(root.dev_dependencies, expected_root.dev_dependencies);
+    assert_eq!(imported.packages, expected.packages);
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{lockfile, resolved_entry};
+use pacm::lockfile::merge::{merge, merge_files, MergeConflict};
+use pacm::lockfile::{load, write, Lockfile};
+
+fn base() -> Lockfile {
+    lockfile(vec![
+        ("", resolved_entry("1.0.0", &[("foo", "^1.0.0"), ("x", "^1.0.0")])),
+        ("node_modules/foo", resolved_entry("1.0.0", &[])),
+        ("node_modules/x", resolved_entry("1.0.0", &[("foo", "^1.0.0")])),
+    ])
+}
+
+#[test]
+fn merge_combines_independent_changes() {
+    let base = base();
+    let mut ours = base.clone();
+    ours.packages.get_mut("").unwrap().dependencies.insert("a".into(), "^1.0.0".into());
+    ours.packages.insert("node_modules/a".into(), resolved_entry("1.0.0", &[]));
+    let mut theirs = base.clone();
+    theirs.packages.get_mut("").unwrap().dependencies.insert("b".into(), "^3.0.0".into());
+    theirs.packages.insert("node_modules/b".into(), resolved_entry("3.1.0", &[]));
+    theirs.packages.remove("node_modules/x");
+    theirs.packages.get_mut("").unwrap().dependencies.remove("x");
+
+    let result = merge(&base, &ours, &theirs);
+    assert!(result.conflicts.is_empty(), "unexpected conflicts: {:?}", result.conflicts);
+    let merged = result.lockfile;
+    let keys: Vec<&str> = merged.packages.keys().map(String::as_str).collect();
+    assert_eq!(keys, vec!["", "node_modules/a", "node_modules/b", "node_modules/foo"]);
+    let root_deps: Vec<&str> =
+        merged.packages[""].dependencies.keys().map(String::as_str).collect();
+    assert_eq!(root_deps, vec!["a", "b", "foo"]);
+}
+
+#[test]
+fn merge_reresolves_entries_changed_on_both_sides() {
+    let base = base();
+    let mut ours = base.clone();
+    ours.packages.insert("node_modules/foo".into(), resolved_entry("1.1.0", &[]));
+    ours.packages.insert("node_modules/x".into(), resolved_entry("1.1.0", &[("foo", "~1.1.0")]));
+    let mut theirs = base.clone();
+    theirs.packages.insert("node_modules/foo".into(), resolved_entry("1.2.0", &[]));
+
+    let result = merge(&base, &ours, &theirs);
+    assert!(result.conflicts.is_empty(), "unexpected conflicts: {:?}", result.conflicts);
+    // 1.2.0 is newer but violates `x`'s `~1.1.0`, so our candidate wins.
+    assert_eq!(result.lockfile.packages["node_modules/foo"], resolved_entry("1.1.0", &[]));
+    assert_eq!(result.lockfile.packages["node_modules/x"].version.as_deref(), Some("1.1.0"));
+}
+
+#[test]
+fn merge_picks_highest_candidate_satisfying_all_ranges() {
+    let base = base();
+    let mut ours = base.clone();
+    ours.packages.insert("node_modules/foo".into(), resolved_entry("1.1.0", &[]));
+    let mut theirs = base.clone();
+    theirs.packages.insert("node_modules/foo".into(), resolved_entry("1.2.0", &[]));
+
+    let result = merge(&base, &ours, &theirs);
+    assert!(result.conflicts.is_empty());
+    assert_eq!(result.lockfile.packages["node_modules/foo"], resolved_entry("1.2.0", &[]));
+}
+
+#[test]
+fn merge_reports_irreconcilable_conflicts() {
+    let base = base();
+    let mut ours = base.clone();
+    ours.packages.insert("node_modules/foo".into(), resolved_entry("1.1.0", &[]));
+    ours.packages.insert("node_modules/x".into(), resolved_entry("1.1.0", &[("foo", "~1.1.0")]));
+    let mut theirs = base.clone();
+    theirs.packages.get_mut("").unwrap().dependencies.insert("foo".into(), "^2.0.0".into());
+    theirs.packages.insert("node_modules/foo".into(), resolved_entry("2.0.0", &[]));
+
+    let result = merge(&base, &ours, &theirs);
+    assert_eq!(
+        result.conflicts,
+        vec![MergeConflict {
+            key: "node_modules/foo".into(),
+            ours: Some("1.1.0".into()),
+            theirs: Some("2.0.0".into()),
+        }]
+    );
+}
+
+#[test]
+fn merge_files_acts_as_git_merge_driver() {
+    let dir = tempfile::tempdir().unwrap();
+    let base_path = dir.path().join("base.lockb");
+    let ours_path = dir.path().join("ours.lockb");
+    let theirs_path = dir.path().join("theirs.lockb");
+
+    let base = base();
+    let mut ours = base.clone();
+    ours.packages.insert("node_modules/a".into(), resolved_entry("1.0.0", &[]));
+    let mut theirs = base.clone();
+    theirs.packages.insert("node_modules/b".into(), resolved_entry("2.0.0", &[]));
+    write(&base, base_path.clone()).unwrap();
+    write(&ours, ours_path.clone()).unwrap();
+    write(&theirs, theirs_path.clone()).unwrap();
+
+    // git passes %O %A %B and expects the result in %A.
+    let clean = merge_files(&base_path, &ours_path, &theirs_path).expect("run merge driver");
+    assert!(clean);
+    let merged = load(&ours_path).unwrap();
+    assert!(merged.packages.contains_key("node_modules/a"));
+    assert!(merged.packages.contains_key("node_modules/b"));
+
+    let mut conflicting = base.clone();
+    conflicting.packages.get_mut("").unwrap().dependencies.insert("foo".into(), "^2.0.0".into());
+    conflicting.packages.insert("node_modules/foo".into(), resolved_entry("2.0.0", &[]));
+    let mut pinned = base.clone();
+    pinned.packages.insert("node_modules/foo".into(), resolved_entry("1.1.0", &[]));
+    pinned.packages.insert("node_modules/x".into(), resolved_entry("1.1.0", &[("foo", "~1.1.0")]));
+    write(&pinned, ours_path.clone()).unwrap();
+    write(&conflicting, theirs_path.clone()).unwrap();
+
+    let clean = merge_files(&base_path, &ours_path, &theirs_path).expect("run merge driver");
+    assert!(!clean);
+    // On conflict our side is left untouched so git marks the file as conflicted.
+    assert_eq!(load(&ours_path).unwrap(), pinned);
+}
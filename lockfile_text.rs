@@ -0,0 +1,87 @@
+mod common;
+
+use common::resolved_entry;
+use pacm::lockfile::text::{export, import, TextFormat};
+use pacm::lockfile::{encode_current_binary, Lockfile, PeerMeta, CURRENT_FORMAT};
+
+fn sample_lockfile() -> Lockfile {
+    let mut lf = Lockfile::default();
+    lf.format = CURRENT_FORMAT;
+    let mut root = resolved_entry("1.0.0", &[]);
+    root.dependencies.insert("b".into(), "^2.0.0".into());
+    root.dev_dependencies.insert("a".into(), "~1.0.0".into());
+    let mut a = resolved_entry("1.0.3", &[]);
+    a.optional_dependencies.insert("fsevents".into(), "^2.3.0".into());
+    a.peer_dependencies.insert("b".into(), ">=2".into());
+    a.peer_dependencies_meta.insert("b".into(), PeerMeta { optional: true });
+    let mut fsevents = resolved_entry("2.3.3", &[]);
+    fsevents.os = vec!["darwin".into()];
+    fsevents.cpu_arch = vec!["arm64".into(), "x64".into()];
+    fsevents.libc = vec!["glibc".into()];
+    let mut b = resolved_entry("2.1.0", &[]);
+    b.store_key = Some("b@2.1.0::abc".into());
+    b.content_hash = Some("abc".into());
+    b.link_mode = Some("link".into());
+    b.store_path = Some("b@2.1.0/abc".into());
+    // Insert out of order; the export must not depend on it.
+    lf.packages.insert("node_modules/fsevents".into(), fsevents);
+    lf.packages.insert("node_modules/b".into(), b);
+    lf.packages.insert(String::new(), root);
+    lf.packages.insert("node_modules/a".into(), a);
+    lf
+}
+
+#[test]
+fn json_export_roundtrips_losslessly() {
+    let lf = sample_lockfile();
+    let text = export(&lf, TextFormat::Json).expect("export json");
+    let imported = import(&text, TextFormat::Json).expect("import json");
+    assert_eq!(imported, lf);
+    assert_eq!(
+        encode_current_binary(&imported).expect("encode imported"),
+        encode_current_binary(&lf).expect("encode original")
+    );
+}
+
+#[test]
+fn yaml_export_roundtrips_losslessly() {
+    let lf = sample_lockfile();
+    let text = export(&lf, TextFormat::Yaml).expect("export yaml");
+    let imported = import(&text, TextFormat::Yaml).expect("import yaml");
+    assert_eq!(imported, lf);
+}
+
+#[test]
+fn text_export_is_sorted_and_stable() {
+    let lf = sample_lockfile();
+    for format in [TextFormat::Json, TextFormat::Yaml] {
+        let first = export(&lf, format).expect("export");
+        let second = export(&lf.clone(), format).expect("export again");
+        assert_eq!(first, second);
+        assert!(first.ends_with('\n'), "text lockfiles end with a newline");
+
+        let positions: Vec<usize> = ["node_modules/a", "node_modules/b", "node_modules/fsevents"]
+            .iter()
+            .map(|key| first.find(key).unwrap_or_else(|| panic!("{key} missing from export")))
+            .collect();
+        let mut sorted = positions.clone();
+        sorted.sort();
+        assert_eq!(positions, sorted, "packages are not sorted by key");
+        // Empty maps and lists are left out to keep diffs small.
+        let empty_fields: [&str; 2] = match format {
+            TextFormat::Json => ["\"os\": []", "\"devDependencies\": {}"],
+            TextFormat::Yaml => ["os: []", "devDependencies: {}"],
+        };
+        for empty in empty_fields {
+            assert!(!first.contains(empty), "{format:?} export contains `{empty}`");
+        }
+    }
+}
+
+#[test]
+fn text_format_parses_cli_names() {
+    assert_eq!("json".parse::<TextFormat>().expect("parse json"), TextFormat::Json);
+    assert_eq!("yaml".parse::<TextFormat>().expect("parse yaml"), TextFormat::Yaml);
+    assert_eq!("yml".parse::<TextFormat>().expect("parse yml"), TextFormat::Yaml);
+    assert!("toml".parse::<TextFormat>().is_err());
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use common::{lockfile, portable_store_path, valid_integrity, valid_resolved_entry, DataHomeGuard};
+use pacm::cache::{cache_package_path, CasStore, EnsureParams};
+use pacm::lockfile::LockfileProblem;
+use pacm::manifest::Manifest;
+use std::fs;
+
+fn manifest(deps: &[(&str, &str)]) -> Manifest {
+    let mut m = Manifest::new("demo".into(), "1.0.0".into());
+    for (name, range) in deps {
+        m.dependencies.insert(name.to_string(), range.to_string());
+    }
+    m
+}
+
+#[test]
+fn consistent_lockfile_has_no_problems() {
+    let m = manifest(&[("a", "^1.0.0")]);
+    let lf = lockfile(vec![
+        ("", valid_resolved_entry("1.0.0", &[("a", "^1.0.0")])),
+        ("node_modules/a", valid_resolved_entry("1.4.0", &[("b", "^2.0.0")])),
+        ("node_modules/b", valid_resolved_entry("2.1.0", &[])),
+    ]);
+    assert_eq!(lf.validate(&m), Vec::<LockfileProblem>::new());
+}
+
+#[test]
+fn manifest_ranges_must_match_locked_versions() {
+    let m = manifest(&[("a", "^2.0.0"), ("missing", "^1.0.0")]);
+    let lf = lockfile(vec![
+        ("", valid_resolved_entry("1.0.0", &[])),
+        ("node_modules/a", valid_resolved_entry("1.4.0", &[])),
+    ]);
+
+    assert_eq!(
+        lf.validate(&m),
+        vec![
+            LockfileProblem::UnsatisfiedRange {
+                from: String::new(),
+                name: "a".into(),
+                range: "^2.0.0".into(),
+                locked: "1.4.0".into(),
+            },
+            LockfileProblem::MissingDependency { from: String::new(), name: "missing".into() },
+        ]
+    );
+}
+
+#[test]
+fn dependency_edges_follow_node_lookup() {
+    let m = manifest(&[("a", "^1.0.0")]);
+    let lf = lockfile(vec![
+        ("", valid_resolved_entry("1.0.0", &[("a", "^1.0.0")])),
+        (
+            "node_modules/a",
+            valid_resolved_entry("1.0.0", &[("b", "^1.0.0"), ("c", "^1.0.0"), ("d", "^2.0.0")]),
+        ),
+        // `a` finds its own nested `b` before the incompatible hoisted one.
+        ("node_modules/a/node_modules/b", valid_resolved_entry("1.2.0", &[])),
+        ("node_modules/b", valid_resolved_entry("3.0.0", &[])),
+        ("node_modules/d", valid_resolved_entry("1.0.0", &[])),
+    ]);
+
+    assert_eq!(
+        lf.validate(&m),
+        vec![
+            LockfileProblem::MissingDependency { from: "node_modules/a".into(), name: "c".into() },
+            LockfileProblem::UnsatisfiedRange {
+                from: "node_modules/a".into(),
+                name: "d".into(),
+                range: "^2.0.0".into(),
+                locked: "1.0.0".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn integrity_strings_must_be_well_formed() {
+    let m = manifest(&[]);
+    let mut lf = lockfile(vec![("", valid_resolved_entry("1.0.0", &[]))]);
+    for (name, integrity) in [
+        ("ok", valid_integrity(7)),
+        ("no-algo", String::from("deadbeef")),
+        ("bad-algo", String::from("md5-AAAA")),
+        ("bad-base64", String::from("sha512-not*base64")),
+        ("short-digest", format!("sha512-{}", STANDARD.encode([0u8; 20]))),
+    ] {
+        let mut e = valid_resolved_entry("1.0.0", &[]);
+        e.integrity = Some(integrity);
+        lf.packages.insert(format!("node_modules/{name}"), e);
+    }
+
+    let flagged: Vec<String> = lf
+        .validate(&m)
+        .into_iter()
+        .map(|problem| match problem {
+            LockfileProblem::MalformedIntegrity { key, .. } => key,
+            other => panic!("unexpected problem {other:?}"),
+        })
+        .collect();
+    assert_eq!(
+        flagged,
+        vec![
+            "node_modules/bad-algo",
+            "node_modules/bad-base64",
+            "node_modules/no-algo",
+            "node_modules/short-digest",
+        ]
+    );
+}
+
+#[test]
+fn store_references_must_exist() {
+    let _sandbox = DataHomeGuard::new();
+    let source = cache_package_path("a", "1.0.0");
+    fs::create_dir_all(&source).expect("create cached package dir");
+    fs::write(source.join("package.json"), r#"{"name":"a","version":"1.0.0"}"#).unwrap();
+    let store = CasStore::open().expect("open cas store");
+    let store_entry = store
+        .ensure_entry(&EnsureParams {
+            name: "a",
+            version: "1.0.0",
+            dependencies: &[],
+            source_dir: source.as_path(),
+            integrity: None,
+            resolved: None,
+        })
+        .expect("ensure store entry");
+
+    let mut good = valid_resolved_entry("1.0.0", &[]);
+    good.store_key = Some(store_entry.store_key.clone());
+    good.store_path = Some(portable_store_path(&store, &store_entry));
+    let mut unknown_key = valid_resolved_entry("1.0.0", &[]);
+    unknown_key.store_key = Some("b@1.0.0::0000".into());
+    let mut dangling_path = valid_resolved_entry("1.0.0", &[]);
+    dangling_path.store_key = Some(store_entry.store_key.clone());
+    dangling_path.store_path = Some("does/not/exist".into());
+    let lf = lockfile(vec![
+        ("node_modules/a", good),
+        ("node_modules/b", unknown_key),
+        ("node_modules/c", dangling_path),
+    ]);
+
+    assert_eq!(
+        lf.validate_store(&store),
+        vec![
+            LockfileProblem::MissingStoreEntry {
+                key: "node_modules/b".into(),
+                store_key: "b@1.0.0::0000".into(),
+            },
+            LockfileProblem::MissingStorePath {
+                key: "node_modules/c".into(),
+                store_path: "does/not/exist".into(),
+            },
+        ]
+    );
+}
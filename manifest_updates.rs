@@ -27,3 +27,17 @@ fn parses_unscoped_without_range() {
     assert_eq!(name, "lodash");
     assert_eq!(range, "*");
 }
+
+#[test]
+fn parses_unscoped_with_git_url() {
+    let (name, range) = parse_spec("widget@git+https://github.com/acme/widget.git#3f2a9c1");
+    assert_eq!(name, "widget");
+    assert_eq!(range, "git+https://github.com/acme/widget.git#3f2a9c1");
+}
+
+#[test]
+fn parses_scoped_with_file_path() {
+    let (name, range) = parse_spec("@acme/local@file:../local");
+    assert_eq!(name, "@acme/local");
+    assert_eq!(range, "file:../local");
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::{resolved_entry, unique_package, DataHomeGuard};
+use pacm::cache::{cache_metadata_path, cache_package_path, CasStore, EnsureParams};
+use pacm::fetcher::{Fetcher, NetworkMode};
+use pacm::installer::offline::check_offline;
+use pacm::lockfile::Lockfile;
+use std::fs;
+
+fn cache_package(name: &str, version: &str) {
+    let dir = cache_package_path(name, version);
+    fs::create_dir_all(&dir).expect("create cached package dir");
+    let manifest = serde_json::json!({ "name": name, "version": version });
+    fs::write(dir.join("package.json"), manifest.to_string()).expect("write package.json");
+}
+
+/// Nothing listens on the discard port, so any request that reaches it fails fast.
+const UNREACHABLE_REGISTRY: &str = "http://127.0.0.1:9";
+
+fn project_lock(packages: &[(&str, &str)]) -> Lockfile {
+    let mut lock = Lockfile::default();
+    lock.packages.insert(String::new(), resolved_entry("0.0.0", &[]));
+    for (name, version) in packages {
+        lock.packages.insert(format!("node_modules/{name}"), resolved_entry(version, &[]));
+    }
+    lock
+}
+
+#[test]
+fn offline_check_passes_when_everything_is_cached() {
+    let _sandbox = DataHomeGuard::new();
+    let pad = unique_package("left-pad");
+    let util = format!("@scope/{}", unique_package("util"));
+    cache_package(pad.as_str(), "1.3.0");
+    cache_package(util.as_str(), "2.0.0");
+    let store = CasStore::open().expect("open cas store");
+
+    let lock = project_lock(&[(pad.as_str(), "1.3.0"), (util.as_str(), "2.0.0")]);
+    check_offline(&lock, &store).expect("all packages available offline");
+}
+
+#[test]
+fn offline_check_lists_every_missing_package() {
+    let _sandbox = DataHomeGuard::new();
+    let pad = unique_package("left-pad");
+    let chalk = unique_package("chalk");
+    let util = format!("@scope/{}", unique_package("util"));
+    let ansi = unique_package("ansi");
+    cache_package(pad.as_str(), "1.3.0");
+    // A different version in the cache must not satisfy the lockfile.
+    cache_package(chalk.as_str(), "4.1.2");
+    let store = CasStore::open().expect("open cas store");
+
+    let mut lock = project_lock(&[
+        (pad.as_str(), "1.3.0"),
+        (chalk.as_str(), "5.3.0"),
+        (util.as_str(), "2.0.0"),
+    ]);
+    lock.packages
+        .insert(format!("node_modules/{chalk}/node_modules/{ansi}"), resolved_entry("6.0.0", &[]));
+
+    let err = check_offline(&lock, &store).expect_err("missing packages must fail");
+    let packages = err.missing_packages();
+    assert_eq!(
+        packages,
+        [format!("{util}@2.0.0"), format!("{ansi}@6.0.0"), format!("{chalk}@5.3.0")]
+    );
+    let message = err.to_string();
+    for missing in packages {
+        assert!(message.contains(missing.as_str()), "{missing} not in: {message}");
+    }
+}
+
+#[test]
+fn offline_check_accepts_store_entries_without_cache() {
+    let _sandbox = DataHomeGuard::new();
+    let ms = unique_package("ms");
+    cache_package(ms.as_str(), "2.1.3");
+    let store = CasStore::open().expect("open cas store");
+    let source_dir = cache_package_path(ms.as_str(), "2.1.3");
+    let store_entry = store
+        .ensure_entry(&EnsureParams {
+            name: &ms,
+            version: "2.1.3",
+            dependencies: &[],
+            source_dir: source_dir.as_path(),
+            integrity: Some("sha512-2.1.3"),
+            resolved: Some("https://registry.example/pkg-2.1.3.tgz"),
+        })
+        .expect("ensure ms store entry");
+    fs::remove_dir_all(&source_dir).expect("evict cached tarball contents");
+
+    let mut lock = project_lock(&[(ms.as_str(), "2.1.3")]);
+    lock.packages.get_mut(&format!("node_modules/{ms}")).unwrap().store_key =
+        Some(store_entry.store_key);
+    check_offline(&lock, &store).expect("store entry satisfies offline install");
+}
+
+#[test]
+fn prefer_offline_serves_cached_package_without_network() {
+    let _sandbox = DataHomeGuard::new();
+    let pad = unique_package("left-pad");
+    cache_package(&pad, "1.3.0");
+
+    let fetcher = Fetcher::new(UNREACHABLE_REGISTRY).with_network_mode(NetworkMode::PreferOffline);
+    let dir = fetcher.ensure_package(&pad, "1.3.0", None).expect("served from cache");
+    assert_eq!(dir, cache_package_path(&pad, "1.3.0"));
+}
+
+#[test]
+fn prefer_offline_reads_cached_registry_metadata() {
+    let _sandbox = DataHomeGuard::new();
+    let pad = unique_package("left-pad");
+    let metadata = serde_json::json!({
+        "name": pad,
+        "dist-tags": { "latest": "1.3.0" },
+        "versions": { "1.3.0": { "name": pad, "version": "1.3.0" } }
+    });
+    let path = cache_metadata_path(&pad);
+    fs::create_dir_all(path.parent().unwrap()).expect("create metadata cache dir");
+    fs::write(&path, metadata.to_string()).expect("write cached metadata");
+
+    let fetcher = Fetcher::new(UNREACHABLE_REGISTRY).with_network_mode(NetworkMode::PreferOffline);
+    let loaded = fetcher.package_metadata(&pad).expect("metadata served from cache");
+    assert_eq!(loaded["dist-tags"]["latest"], "1.3.0");
+}
+
+#[test]
+fn prefer_offline_falls_back_to_registry_on_miss() {
+    let _sandbox = DataHomeGuard::new();
+    let chalk = unique_package("chalk");
+
+    let prefer = Fetcher::new(UNREACHABLE_REGISTRY).with_network_mode(NetworkMode::PreferOffline);
+    let err = prefer.ensure_package(&chalk, "5.3.0", None).expect_err("registry unreachable");
+    let message = format!("{err:#}");
+    assert!(message.contains(UNREACHABLE_REGISTRY), "registry was not consulted: {message}");
+
+    // Strict offline mode must fail without ever touching the registry.
+    let offline = Fetcher::new(UNREACHABLE_REGISTRY).with_network_mode(NetworkMode::Offline);
+    let err = offline.ensure_package(&chalk, "5.3.0", None).expect_err("not cached");
+    let message = format!("{err:#}");
+    assert!(message.contains(&format!("{chalk}@5.3.0")), "unexpected error: {message}");
+    assert!(!message.contains(UNREACHABLE_REGISTRY), "offline mode hit the registry: {message}");
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use common::package_entry;
+use pacm::installer::peers::{check_peers, PeerIssue, PeerOptions};
+use pacm::lockfile::{Lockfile, PackageEntry, PeerMeta};
+
+fn with_peer(mut entry: PackageEntry, peer: &str, range: &str, optional: bool) -> PackageEntry {
+    entry.peer_dependencies.insert(peer.to_string(), range.to_string());
+    if optional {
+        entry.peer_dependencies_meta.insert(peer.to_string(), PeerMeta { optional: true });
+    }
+    entry
+}
+
+fn lock_with(entries: Vec<(&str, PackageEntry)>) -> Lockfile {
+    let mut lock = Lockfile::default();
+    lock.packages.insert(String::new(), package_entry("0.0.0", &[]));
+    for (key, entry) in entries {
+        lock.packages.insert(key.to_string(), entry);
+    }
+    lock
+}
+
+#[test]
+fn satisfied_peers_report_nothing() {
+    let lock = lock_with(vec![
+        ("node_modules/react", package_entry("18.2.0", &[])),
+        (
+            "node_modules/react-dom",
+            with_peer(package_entry("18.2.0", &[]), "react", "^18.2.0", false),
+        ),
+    ]);
+    let report = check_peers(&lock, &PeerOptions::default());
+    assert!(report.issues.is_empty(), "unexpected issues: {:?}", report.issues);
+    assert!(report.to_install.is_empty());
+}
+
+#[test]
+fn missing_required_peer_is_reported() {
+    let plugin = with_peer(package_entry("1.0.0", &[]), "eslint", ">=8", false);
+    let plugin = with_peer(plugin, "typescript", ">=5", true);
+    let lock = lock_with(vec![("node_modules/eslint-plugin-demo", plugin)]);
+
+    let report = check_peers(&lock, &PeerOptions { auto_install_peers: false });
+    // Optional peers may be absent without complaint.
+    assert_eq!(
+        report.issues,
+        vec![PeerIssue::Missing {
+            package: "node_modules/eslint-plugin-demo".into(),
+            peer: "eslint".into(),
+            range: ">=8".into(),
+        }]
+    );
+    assert!(report.issues[0].to_string().contains("eslint@>=8"));
+}
+
+#[test]
+fn installed_peer_outside_range_is_incompatible() {
+    let lock = lock_with(vec![
+        ("node_modules/react", package_entry("17.0.2", &[])),
+        (
+            "node_modules/react-dom",
+            with_peer(package_entry("18.2.0", &[]), "react", "^18.2.0", false),
+        ),
+        // Optional peers that are installed must still match their range.
+        (
+            "node_modules/swr",
+            with_peer(package_entry("2.2.0", &[]), "react", "^16.11.0 || ^18.0.0", true),
+        ),
+    ]);
+
+    let report = check_peers(&lock, &PeerOptions::default());
+    assert_eq!(
+        report.issues,
+        vec![
+            PeerIssue::Incompatible {
+                package: "node_modules/react-dom".into(),
+                peer: "react".into(),
+                range: "^18.2.0".into(),
+                installed: "17.0.2".into(),
+            },
+            PeerIssue::Incompatible {
+                package: "node_modules/swr".into(),
+                peer: "react".into(),
+                range: "^16.11.0 || ^18.0.0".into(),
+                installed: "17.0.2".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn auto_install_collects_missing_required_peers() {
+    let lock = lock_with(vec![
+        (
+            "node_modules/react-dom",
+            with_peer(package_entry("18.2.0", &[]), "react", "^18.2.0", false),
+        ),
+        (
+            "node_modules/react-query",
+            with_peer(package_entry("3.39.0", &[]), "react", "^18.2.0", false),
+        ),
+        ("node_modules/swr", with_peer(package_entry("2.2.0", &[]), "scheduler", "*", true)),
+    ]);
+
+    let report = check_peers(&lock, &PeerOptions { auto_install_peers: true });
+    assert!(report.issues.is_empty(), "unexpected issues: {:?}", report.issues);
+    // Several dependents asking for the same peer produce a single request.
+    assert_eq!(report.to_install, vec![(String::from("react"), String::from("^18.2.0"))]);
+}
+
+#[test]
+fn auto_install_reports_conflicting_peer_ranges() {
+    let lock = lock_with(vec![
+        (
+            "node_modules/legacy-ui",
+            with_peer(package_entry("1.0.0", &[]), "react", "^16.0.0", false),
+        ),
+        (
+            "node_modules/react-dom",
+            with_peer(package_entry("18.2.0", &[]), "react", "^18.2.0", false),
+        ),
+    ]);
+
+    let report = check_peers(&lock, &PeerOptions { auto_install_peers: true });
+    assert!(report.to_install.is_empty());
+    assert_eq!(
+        report.issues,
+        vec![PeerIssue::Conflict {
+            peer: "react".into(),
+            requested: vec![
+                ("node_modules/legacy-ui".into(), "^16.0.0".into()),
+                ("node_modules/react-dom".into(), "^18.2.0".into()),
+            ],
+        }]
+    );
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::package_entry;
+use pacm::installer::Platform;
+use pacm::lockfile::PackageEntry;
+
+fn constrained_entry(os: &[&str], cpu: &[&str], libc: &[&str]) -> PackageEntry {
+    PackageEntry {
+        os: os.iter().map(|s| s.to_string()).collect(),
+        cpu_arch: cpu.iter().map(|s| s.to_string()).collect(),
+        libc: libc.iter().map(|s| s.to_string()).collect(),
+        ..package_entry("1.0.0", &[])
+    }
+}
+
+fn platform(os: &str, cpu: &str, libc: Option<&str>) -> Platform {
+    Platform { os: os.to_string(), cpu: cpu.to_string(), libc: libc.map(str::to_string) }
+}
+
+#[test]
+fn unconstrained_packages_match_everywhere() {
+    let entry = constrained_entry(&[], &[], &[]);
+    assert!(platform("linux", "x64", Some("glibc")).supports(&entry));
+    assert!(platform("win32", "arm64", None).supports(&entry));
+}
+
+#[test]
+fn allow_lists_match_os_and_cpu() {
+    let entry = constrained_entry(&["darwin"], &["arm64", "x64"], &[]);
+    assert!(platform("darwin", "arm64", None).supports(&entry));
+    assert!(!platform("linux", "arm64", Some("glibc")).supports(&entry));
+    assert!(!platform("darwin", "ia32", None).supports(&entry));
+}
+
+#[test]
+fn negations_exclude_targets() {
+    let entry = constrained_entry(&["!win32"], &["!arm"], &[]);
+    assert!(platform("linux", "x64", Some("glibc")).supports(&entry));
+    assert!(!platform("win32", "x64", None).supports(&entry));
+    assert!(!platform("linux", "arm", Some("glibc")).supports(&entry));
+}
+
+#[test]
+fn libc_only_constrains_targets_that_report_one() {
+    let entry = constrained_entry(&["linux"], &["x64"], &["musl"]);
+    assert!(platform("linux", "x64", Some("musl")).supports(&entry));
+    assert!(!platform("linux", "x64", Some("glibc")).supports(&entry));
+    // npm ignores `libc` when the target has no libc (e.g. cross-installing for macOS).
+    let darwin_entry = constrained_entry(&[], &[], &["glibc"]);
+    assert!(platform("darwin", "arm64", None).supports(&darwin_entry));
+}
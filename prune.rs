@@ -0,0 +1,101 @@
+mod common;
+
+use common::package_entry;
+use pacm::installer::prune::{prune, PruneOptions};
+use pacm::lockfile::Lockfile;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+fn installed_package(root: &Path, name: &str, bin: Option<&str>) -> PathBuf {
+    let mut dir = root.join("node_modules");
+    for part in name.split('/') {
+        dir.push(part);
+    }
+    fs::create_dir_all(&dir).expect("create installed package dir");
+    let mut manifest = serde_json::json!({ "name": name, "version": "1.0.0" });
+    if let Some(bin) = bin {
+        manifest["bin"][bin] = serde_json::json!("cli.js");
+        let bin_dir = root.join("node_modules").join(".bin");
+        fs::create_dir_all(&bin_dir).expect("create .bin dir");
+        fs::write(bin_dir.join(bin), "#!/bin/sh\n").expect("write bin shim");
+    }
+    fs::write(dir.join("package.json"), manifest.to_string()).expect("write package.json");
+    dir
+}
+
+/// Root depends on `kept` (which pulls in `shared`) and dev-depends on `jest`.
+fn project_lock() -> Lockfile {
+    let mut lock = Lockfile::default();
+    let mut root = package_entry("1.0.0", &[("kept", "^1.0.0")]);
+    root.dev_dependencies.insert("jest".into(), "^29.0.0".into());
+    lock.packages.insert(String::new(), root);
+    lock.packages
+        .insert("node_modules/kept".into(), package_entry("1.0.0", &[("shared", "^1.0.0")]));
+    lock.packages.insert("node_modules/shared".into(), package_entry("1.0.0", &[]));
+    lock.packages.insert(
+        "node_modules/jest".into(),
+        package_entry("29.0.0", &[("jest-util", "^29.0.0"), ("shared", "^1.0.0")]),
+    );
+    lock.packages.insert("node_modules/jest-util".into(), package_entry("29.0.0", &[]));
+    lock
+}
+
+#[test]
+fn prune_removes_packages_missing_from_lockfile() {
+    let project = tempdir().expect("create project dir");
+    let root = project.path();
+    for name in ["shared", "jest", "jest-util"] {
+        installed_package(root, name, None);
+    }
+    let kept = installed_package(root, "kept", Some("kept-cli"));
+    let stale = installed_package(root, "stale", Some("stale-cli"));
+    let scoped = installed_package(root, "@scope/gone", None);
+    fs::create_dir_all(root.join("node_modules").join(".cache")).expect("create tool cache");
+
+    let report = prune(root, &project_lock(), &PruneOptions::default()).expect("prune");
+
+    assert_eq!(report.removed_packages, vec!["@scope/gone".to_string(), "stale".to_string()]);
+    assert_eq!(report.removed_bins, vec!["stale-cli".to_string()]);
+    assert!(kept.exists());
+    assert!(!stale.exists());
+    assert!(!scoped.exists());
+    // Empty scope directories go away with their last package.
+    assert!(!root.join("node_modules").join("@scope").exists());
+    assert!(root.join("node_modules").join(".bin").join("kept-cli").exists());
+    assert!(!root.join("node_modules").join(".bin").join("stale-cli").exists());
+    // Dot-directories belong to other tools and are never pruned.
+    assert!(root.join("node_modules").join(".cache").exists());
+}
+
+#[test]
+fn prune_production_drops_dev_only_packages() {
+    let project = tempdir().expect("create project dir");
+    let root = project.path();
+    for name in ["kept", "shared", "jest-util"] {
+        installed_package(root, name, None);
+    }
+    installed_package(root, "jest", Some("jest"));
+
+    let report = prune(root, &project_lock(), &PruneOptions { production: true })
+        .expect("prune --production");
+
+    assert_eq!(report.removed_packages, vec!["jest".to_string(), "jest-util".to_string()]);
+    assert_eq!(report.removed_bins, vec!["jest".to_string()]);
+    // `shared` is also reachable from a production dependency, so it stays.
+    assert!(root.join("node_modules").join("shared").exists());
+    assert!(root.join("node_modules").join("kept").exists());
+}
+
+#[test]
+fn prune_is_noop_when_tree_matches_lockfile() {
+    let project = tempdir().expect("create project dir");
+    let root = project.path();
+    for name in ["kept", "shared", "jest", "jest-util"] {
+        installed_package(root, name, None);
+    }
+
+    let report = prune(root, &project_lock(), &PruneOptions::default()).expect("prune");
+    assert!(report.removed_packages.is_empty());
+    assert!(report.removed_bins.is_empty());
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::package_entry;
+use pacm::lockfile::{
+    decode_current_binary, encode_current_binary, Lockfile, PackageEntry, PackageSource,
+};
+use pacm::resolver::pin_local_source;
+use std::fs;
+
+fn entry(version: &str, source: Option<PackageSource>) -> PackageEntry {
+    PackageEntry { source, ..package_entry(version, &[]) }
+}
+
+#[test]
+fn classifies_dependency_ranges() {
+    assert_eq!(PackageSource::from_range("^1.2.3"), PackageSource::Registry);
+    assert_eq!(
+        PackageSource::from_range("git+https://github.com/acme/widget.git#v1.4.0"),
+        PackageSource::Git {
+            url: "https://github.com/acme/widget.git".into(),
+            reference: Some("v1.4.0".into()),
+            commit: None,
+        }
+    );
+    assert_eq!(
+        PackageSource::from_range("github:acme/widget"),
+        PackageSource::Git {
+            url: "https://github.com/acme/widget.git".into(),
+            reference: None,
+            commit: None,
+        }
+    );
+    assert_eq!(
+        PackageSource::from_range("https://cdn.example/widget-1.0.0.tgz"),
+        PackageSource::Tarball { url: "https://cdn.example/widget-1.0.0.tgz".into() }
+    );
+    assert_eq!(
+        PackageSource::from_range("file:../local"),
+        PackageSource::File { path: "../local".into() }
+    );
+    assert_eq!(
+        PackageSource::from_range("link:../local"),
+        PackageSource::Link { path: "../local".into() }
+    );
+}
+
+#[test]
+fn only_pinned_sources_are_reproducible() {
+    let full_sha = "3f2a9c1d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a39";
+    let unpinned = PackageSource::from_range("git+https://github.com/acme/widget.git#main");
+    assert!(!unpinned.is_pinned());
+    let pinned = PackageSource::Git {
+        url: "https://github.com/acme/widget.git".into(),
+        reference: Some("main".into()),
+        commit: Some(full_sha.into()),
+    };
+    assert!(pinned.is_pinned());
+    assert!(PackageSource::from_range("file:../local").is_pinned());
+    assert!(PackageSource::from_range("https://cdn.example/widget-1.0.0.tgz").is_pinned());
+}
+
+#[test]
+fn lockfile_roundtrips_every_source_kind() {
+    let mut lf = Lockfile::default();
+    lf.packages.insert("node_modules/registry".into(), entry("1.0.0", None));
+    lf.packages.insert(
+        "node_modules/git".into(),
+        entry(
+            "1.4.0",
+            Some(PackageSource::Git {
+                url: "https://github.com/acme/widget.git".into(),
+                reference: Some("v1.4.0".into()),
+                commit: Some("3f2a9c1d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a39".into()),
+            }),
+        ),
+    );
+    lf.packages.insert(
+        "node_modules/tarball".into(),
+        entry(
+            "1.0.0",
+            Some(PackageSource::Tarball { url: "https://cdn.example/widget-1.0.0.tgz".into() }),
+        ),
+    );
+    lf.packages.insert(
+        "node_modules/file".into(),
+        entry("0.1.0", Some(PackageSource::File { path: "packages/file".into() })),
+    );
+    lf.packages.insert(
+        "node_modules/link".into(),
+        entry("0.1.0", Some(PackageSource::Link { path: "../link".into() })),
+    );
+
+    let decoded = decode_current_binary(&encode_current_binary(&lf).unwrap()).unwrap();
+    assert_eq!(decoded, lf);
+}
+
+#[test]
+fn local_sources_are_pinned_relative_to_project() {
+    let dir = tempfile::tempdir().unwrap();
+    let project = dir.path().join("app");
+    fs::create_dir_all(project.join("packages").join("util")).unwrap();
+    fs::create_dir_all(dir.path().join("shared")).unwrap();
+
+    let pinned =
+        pin_local_source(&project, &PackageSource::from_range("file:./packages/../packages/util"))
+            .expect("pin file source");
+    assert_eq!(pinned, PackageSource::File { path: "packages/util".into() });
+
+    let pinned = pin_local_source(&project, &PackageSource::from_range("link:../shared"))
+        .expect("pin link source");
+    assert_eq!(pinned, PackageSource::Link { path: "../shared".into() });
+
+    let err = pin_local_source(&project, &PackageSource::from_range("file:../missing"))
+        .expect_err("missing directories cannot be pinned");
+    assert!(err.to_string().contains("../missing"), "unexpected error: {err}");
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::package_entry;
+use pacm::cli::commands::why::{explain, render_json, render_text};
+use pacm::lockfile::graph::DependencyKind;
+use pacm::lockfile::Lockfile;
+use pacm::manifest::Manifest;
+
+/// demo -> app-lib -(optional)-> b@1; demo -(dev)-> plugin -(peer)-> b@2; demo -> b@2
+fn project() -> (Manifest, Lockfile) {
+    let mut manifest = Manifest::new("demo".into(), "1.0.0".into());
+    manifest.dependencies.insert("app-lib".into(), "^1.0.0".into());
+    manifest.dependencies.insert("b".into(), "^2.0.0".into());
+    manifest.dev_dependencies.insert("plugin".into(), "^1.0.0".into());
+
+    let mut lock = Lockfile::default();
+    lock.sync_from_manifest(&manifest);
+    let mut app_lib = package_entry("1.0.0", &[]);
+    app_lib.optional_dependencies.insert("b".into(), "^1.0.0".into());
+    let mut plugin = package_entry("1.3.0", &[]);
+    plugin.peer_dependencies.insert("b".into(), ">=2".into());
+    lock.packages.insert("node_modules/app-lib".into(), app_lib);
+    lock.packages.insert("node_modules/app-lib/node_modules/b".into(), package_entry("1.0.0", &[]));
+    lock.packages.insert("node_modules/b".into(), package_entry("2.0.0", &[]));
+    lock.packages.insert("node_modules/plugin".into(), plugin);
+    (manifest, lock)
+}
+
+#[test]
+fn why_finds_every_path_with_edge_kinds() {
+    let (manifest, lock) = project();
+    let paths = explain(&manifest, &lock, "b");
+
+    let summary: Vec<Vec<(String, String, DependencyKind)>> = paths
+        .iter()
+        .map(|path| {
+            path.steps.iter().map(|s| (s.name.clone(), s.version.clone(), s.kind)).collect()
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            vec![
+                ("app-lib".into(), "1.0.0".into(), DependencyKind::Prod),
+                ("b".into(), "1.0.0".into(), DependencyKind::Optional),
+            ],
+            vec![("b".into(), "2.0.0".into(), DependencyKind::Prod)],
+            vec![
+                ("plugin".into(), "1.3.0".into(), DependencyKind::Dev),
+                ("b".into(), "2.0.0".into(), DependencyKind::Peer),
+            ],
+        ]
+    );
+    assert!(explain(&manifest, &lock, "unknown").is_empty());
+}
+
+#[test]
+fn why_renders_text_report() {
+    let (manifest, lock) = project();
+    let text = render_text(&manifest, &explain(&manifest, &lock, "b"));
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "b@1.0.0",
+            "  demo@1.0.0 > app-lib@1.0.0 > (optional) b@1.0.0",
+            "b@2.0.0",
+            "  demo@1.0.0 > b@2.0.0",
+            "  demo@1.0.0 > (dev) plugin@1.3.0 > (peer) b@2.0.0",
+        ]
+    );
+}
+
+#[test]
+fn why_renders_json_report() {
+    let (manifest, lock) = project();
+    let json = render_json(&explain(&manifest, &lock, "b")).expect("render json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+
+    let paths = value.as_array().expect("array of paths");
+    assert_eq!(paths.len(), 3);
+    assert_eq!(paths[2]["name"], "b");
+    assert_eq!(paths[2]["version"], "2.0.0");
+    assert_eq!(paths[2]["location"], "node_modules/b");
+    assert_eq!(
+        paths[2]["path"],
+        serde_json::json!([
+            { "name": "plugin", "version": "1.3.0", "kind": "dev" },
+            { "name": "b", "version": "2.0.0", "kind": "peer" }
+        ])
+    );
+}